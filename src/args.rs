@@ -1,9 +1,9 @@
 use std::path::PathBuf;
 
-use anyhow::Context;
-use chorts::{Features, Target};
 use clap::{Arg, ArgAction};
 
+use crate::visit::{FileFilter, ItemFilter};
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ClassifyKind {
     AssociatedConstant,
@@ -21,9 +21,84 @@ pub enum ClassifyKind {
     TypeAlias,
     Variant,
     Static,
+    Module,
+    Union,
+    ForeignFunction,
+    ForeignStatic,
+    ForeignType,
+    TraitAlias,
+    AssociatedItem,
+
+    Markdown,
+    LongParagraph,
+    LazyContinuation,
+    EmptyLine,
+    Tabs,
+    QuotedLink,
+    Overindented,
+    DoctestMain,
+
+    FailingDoctest,
+    IgnoredDoctest,
+
+    Examples,
+    Section,
+    Placeholder,
+    Suppressed,
+    Feature,
+    DocCfg,
+    Metadata,
+    Readme,
+
+    // messages that don't match any of the other kinds
+    Other,
 }
 
 impl ClassifyKind {
+    pub const ALL: &[Self] = &[
+        Self::AssociatedConstant,
+        Self::AssociatedFunction,
+        Self::AssociatedType,
+        Self::Constant,
+        Self::Crate,
+        Self::Enum,
+        Self::Function,
+        Self::Macro,
+        Self::Method,
+        Self::Struct,
+        Self::StructField,
+        Self::Trait,
+        Self::TypeAlias,
+        Self::Variant,
+        Self::Static,
+        Self::Module,
+        Self::Union,
+        Self::ForeignFunction,
+        Self::ForeignStatic,
+        Self::ForeignType,
+        Self::TraitAlias,
+        Self::AssociatedItem,
+        Self::Markdown,
+        Self::LongParagraph,
+        Self::LazyContinuation,
+        Self::EmptyLine,
+        Self::Tabs,
+        Self::QuotedLink,
+        Self::Overindented,
+        Self::DoctestMain,
+        Self::FailingDoctest,
+        Self::IgnoredDoctest,
+        Self::Examples,
+        Self::Section,
+        Self::Placeholder,
+        Self::Suppressed,
+        Self::Feature,
+        Self::DocCfg,
+        Self::Metadata,
+        Self::Readme,
+        Self::Other,
+    ];
+
     pub const fn as_key(&self) -> &'static str {
         match self {
             Self::AssociatedConstant => "associated_constant",
@@ -41,79 +116,165 @@ impl ClassifyKind {
             Self::TypeAlias => "type_alias",
             Self::Variant => "variant",
             Self::Static => "static",
+            Self::Module => "module",
+            Self::Union => "union",
+            Self::ForeignFunction => "foreign_function",
+            Self::ForeignStatic => "foreign_static",
+            Self::ForeignType => "foreign_type",
+            Self::TraitAlias => "trait_alias",
+            Self::AssociatedItem => "associated_item",
+            Self::Markdown => "markdown",
+            Self::LongParagraph => "long_paragraph",
+            Self::LazyContinuation => "lazy_continuation",
+            Self::EmptyLine => "empty_line",
+            Self::Tabs => "tabs",
+            Self::QuotedLink => "quoted_link",
+            Self::Overindented => "overindented",
+            Self::DoctestMain => "doctest_main",
+            Self::FailingDoctest => "failing_doctest",
+            Self::IgnoredDoctest => "ignored_doctest",
+            Self::Examples => "examples",
+            Self::Section => "section",
+            Self::Placeholder => "placeholder",
+            Self::Suppressed => "suppressed",
+            Self::Feature => "feature",
+            Self::DocCfg => "doc_cfg",
+            Self::Metadata => "metadata",
+            Self::Readme => "readme",
+            Self::Other => "other",
         }
     }
 
-    pub const fn as_str(&self) -> &'static str {
+    // the doc quality lints have several messages, so these are all of the
+    // suffixes a message can have for this kind
+    pub const fn suffixes(&self) -> &'static [&'static str] {
         match self {
-            Self::AssociatedConstant => "associated constant",
-            Self::AssociatedFunction => "associated function",
-            Self::AssociatedType => "associated type",
-            Self::Constant => "constant",
-            Self::Crate => "the crate",
-            Self::Enum => "enum",
-            Self::Function => "function",
-            Self::Macro => "macro",
-            Self::Method => "method",
-            Self::Struct => "struct",
-            Self::StructField => "struct field",
-            Self::Trait => "trait",
-            Self::TypeAlias => "type alias",
-            Self::Variant => "variant",
-            Self::Static => "static",
+            Self::AssociatedConstant => &["associated constant"],
+            Self::AssociatedFunction => &["associated function"],
+            Self::AssociatedType => &["associated type"],
+            Self::Constant => &["constant"],
+            Self::Crate => &["the crate"],
+            Self::Enum => &["enum"],
+            Self::Function => &["function"],
+            Self::Macro => &["macro"],
+            Self::Method => &["method"],
+            Self::Struct => &["struct"],
+            Self::StructField => &["struct field"],
+            Self::Trait => &["trait"],
+            Self::TypeAlias => &["type alias"],
+            Self::Variant => &["variant"],
+            Self::Static => &["static"],
+            Self::Module => &["module"],
+            Self::Union => &["union"],
+            Self::ForeignFunction => &["foreign function"],
+            Self::ForeignStatic => &["foreign static"],
+            Self::ForeignType => &["foreign type"],
+            Self::TraitAlias => &["trait alias"],
+            Self::AssociatedItem => &["associated item"],
+            Self::Markdown => &["missing backticks", "make a proper Markdown link"],
+            Self::LongParagraph => &["paragraph is too long"],
+            Self::LazyContinuation => &["without indentation", "without `>` marker"],
+            Self::EmptyLine => &["after doc comment", "after doc comments"],
+            Self::Tabs => &["tabs in doc comments is not recommended"],
+            Self::QuotedLink => &["using quotes instead of backticks"],
+            Self::Overindented => &["overindented"],
+            Self::DoctestMain => &["in doctest"],
+            Self::FailingDoctest => &["failing doctest"],
+            Self::IgnoredDoctest => &["ignored doctest"],
+            Self::Examples => &["has no examples"],
+            // clippy's `# Errors`, `# Panics` and `# Safety` messages also end with `section`
+            Self::Section => &["a required section"],
+            Self::Placeholder => &["placeholder docs"],
+            Self::Suppressed => &["public items", "public item"],
+            Self::Feature => &["has no description"],
+            Self::DocCfg => &["without `doc(cfg)`"],
+            Self::Metadata => &["metadata is missing", "metadata is empty"],
+            Self::Readme => &["isn't included in the crate docs"],
+            Self::Other => &[],
         }
     }
 
-    pub fn parse(input: &str) -> Option<(Self, usize)> {
-        for (k, v) in [
-            Self::AssociatedConstant,
-            Self::AssociatedFunction,
-            Self::AssociatedType,
-            Self::Constant,
-            Self::Crate,
-            Self::Enum,
-            Self::Function,
-            Self::Macro,
-            Self::Method,
-            Self::Struct,
-            Self::StructField,
-            Self::Trait,
-            Self::TypeAlias,
-            Self::Variant,
-            Self::Static,
-        ]
-        .iter()
-        .copied()
-        .map(|this| (this.as_str(), this))
-        {
-            let Some(p) = input.strip_suffix(k) else {
-                continue;
-            };
-            return Some((v, p.len()));
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|kind| kind.as_key() == key)
+    }
+
+    pub fn matches(&self, input: &str) -> bool {
+        match Self::parse(input) {
+            Some((kind, _)) => kind == *self,
+            None => *self == Self::Other,
         }
-        None
+    }
+
+    // the longest suffix wins, so `foreign function` isn't a `function`
+    pub fn parse(input: &str) -> Option<(Self, usize)> {
+        Self::ALL
+            .iter()
+            .copied()
+            .flat_map(|this| this.suffixes().iter().map(move |s| (*s, this)))
+            .filter(|(k, _)| input.ends_with(k))
+            .max_by_key(|(k, _)| k.len())
+            .map(|(k, v)| (v, input.len() - k.len()))
     }
 }
 
 impl clap::ValueEnum for ClassifyKind {
     fn value_variants<'a>() -> &'a [Self] {
-        &[
-            Self::AssociatedConstant,
-            Self::AssociatedFunction,
-            Self::AssociatedType,
-            Self::Constant,
-            Self::Crate,
-            Self::Enum,
-            Self::Function,
-            Self::Macro,
-            Self::Method,
-            Self::Struct,
-            Self::StructField,
-            Self::Trait,
-            Self::TypeAlias,
-            Self::Variant,
-            Self::Static,
-        ]
+        Self::ALL
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(self.as_key()))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Visibility {
+    Public,
+    Private,
+}
+
+impl Visibility {
+    pub const fn as_key(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Private => "private",
+        }
+    }
+}
+
+impl clap::ValueEnum for Visibility {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Public, Self::Private]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(self.as_key()))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    #[default]
+    File,
+    Visibility,
+    Author,
+    Owner,
+}
+
+impl GroupBy {
+    pub const fn as_key(&self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Visibility => "visibility",
+            Self::Author => "author",
+            Self::Owner => "owner",
+        }
+    }
+}
+
+impl clap::ValueEnum for GroupBy {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::File, Self::Visibility, Self::Author, Self::Owner]
     }
 
     fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
@@ -121,27 +282,100 @@ impl clap::ValueEnum for ClassifyKind {
     }
 }
 
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Html,
+}
+
+impl Format {
+    pub const fn as_key(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Html => "html",
+        }
+    }
+}
+
+impl clap::ValueEnum for Format {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Text, Self::Html]
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(self.as_key()))
+    }
+}
+
+// how the features are combined when checking each combination on its own
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FeatureMatrix {
+    // no features, then each feature alone
+    Each,
+    // every combination of the features
+    Powerset,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Subcommand {
+    // without `yes` this only shows the changes
+    Fix { yes: bool },
+    // the history file is relative to the manifest
+    History { command: History, file: PathBuf },
+    Badge { output: PathBuf },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum History {
+    Record,
+    Show,
+}
+
 #[derive(Debug)]
 pub struct Args {
+    pub command: Option<Subcommand>,
     pub path: PathBuf,
     pub errors: bool,
     pub panics: bool,
     pub safety: bool,
+    pub quality: bool,
+    pub private: bool,
+    pub rustdoc: bool,
+    pub doctests: bool,
+    pub require_examples: bool,
+    pub placeholders: bool,
+    pub audit_allows: bool,
+    pub feature_docs: bool,
+    pub manifest_check: bool,
 
-    pub target: Target,
-    pub features: Features,
+    // the target and feature selection, for running cargo directly
+    pub target_args: Vec<String>,
+    pub feature_args: Vec<String>,
+    pub feature_matrix: Option<FeatureMatrix>,
 
     pub show_item: bool,
     pub compact: bool,
+    pub show_origin: bool,
+    pub blame: bool,
+    pub older_than: Option<std::time::Duration>,
+    pub owners: Vec<String>,
+    pub group_by: GroupBy,
+    pub format: Format,
+    pub output: Option<PathBuf>,
     pub nightly: bool,
+    pub override_lints: bool,
 
     pub ignore_config: bool,
     pub print_default_config: bool,
     pub print_config_path: bool,
 
-    pub filter: Vec<PathBuf>,
+    pub filter: FileFilter,
+    pub items: ItemFilter,
+    pub lints: Vec<String>,
     pub include: Vec<ClassifyKind>,
     pub exclude: Vec<ClassifyKind>,
+    pub visibility: Option<Visibility>,
 }
 
 impl Args {
@@ -149,6 +383,51 @@ impl Args {
         let cmd = clap::Command::new(env!("CARGO_PKG_NAME"))
             .version(env!("CARGO_PKG_VERSION"))
             .about("reports where documentation is missing")
+            .subcommand(
+                clap::Command::new("fix")
+                    .about("turns regular comments above undocumented items into doc comments")
+                    .arg(
+                        Arg::new("yes")
+                            .long("yes")
+                            .action(ArgAction::SetTrue)
+                            .help("writes the changes instead of only showing them"),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("history")
+                    .about("tracks the documentation debt over time")
+                    .subcommand_required(true)
+                    .subcommand(
+                        clap::Command::new("record")
+                            .about("appends a snapshot of the current results to the history"),
+                    )
+                    .subcommand(
+                        clap::Command::new("show")
+                            .about("shows the trend of the recorded snapshots"),
+                    )
+                    .arg(
+                        Arg::new("file")
+                            .long("file")
+                            .global(true)
+                            .help("the history file, relative to the manifest")
+                            .value_parser(clap::value_parser!(PathBuf))
+                            .default_value(".cds-history.jsonl")
+                            .action(ArgAction::Set),
+                    ),
+            )
+            .subcommand(
+                clap::Command::new("badge")
+                    .about("writes an svg badge with the documentation coverage")
+                    .arg(
+                        Arg::new("output")
+                            .long("output")
+                            .short('o')
+                            .help("where to write the badge")
+                            .value_parser(clap::value_parser!(PathBuf))
+                            .default_value("docs.svg")
+                            .action(ArgAction::Set),
+                    ),
+            )
             .arg(
                 Arg::new("path")
                     .long("manifest-path")
@@ -163,6 +442,12 @@ impl Args {
                     .action(ArgAction::SetTrue)
                     .help("use the nightly version of the toolchain"),
             )
+            .arg(
+                Arg::new("override_lints")
+                    .long("override-lints")
+                    .action(ArgAction::SetTrue)
+                    .help("check the lints even if the project allows them"),
+            )
             .arg(
                 Arg::new("errors")
                     .long("error")
@@ -185,210 +470,106 @@ impl Args {
                     .action(ArgAction::SetTrue),
             )
             .arg(
-                Arg::new("all")
-                    .short('a')
-                    .long("all")
-                    .help("enables all extra warnings")
+                Arg::new("quality")
+                    .long("quality")
+                    .help("checks the quality of existing documentation")
                     .help_heading("extra warnings")
                     .action(ArgAction::SetTrue),
             )
-            //
             .arg(
-                Arg::new("lib")
-                    .long("lib")
-                    .help_heading("targets")
-                    .conflicts_with_all([
-                        "bins",
-                        "bin",
-                        "examples",
-                        "example",
-                        "tests",
-                        "test",
-                        "benches",
-                        "bench",
-                        "all_targets",
-                    ])
-                    .action(ArgAction::SetTrue)
-                    .help("check only this package's library"),
-            )
-            .arg(
-                Arg::new("bins")
-                    .long("bins")
-                    .help_heading("targets")
-                    .conflicts_with_all([
-                        "lib",
-                        "bin",
-                        "examples",
-                        "example",
-                        "tests",
-                        "test",
-                        "benches",
-                        "bench",
-                        "all_targets",
-                    ])
-                    .action(ArgAction::SetTrue)
-                    .help("check all binaries"),
-            )
-            .arg(
-                Arg::new("bin")
-                    .help_heading("targets")
-                    .conflicts_with_all([
-                        "lib",
-                        "bins",
-                        "examples",
-                        "example",
-                        "tests",
-                        "test",
-                        "benches",
-                        "bench",
-                        "all_targets",
-                    ])
-                    .long("bin")
-                    .help("check only the specified binary")
-                    .action(ArgAction::Set),
+                Arg::new("private")
+                    .long("private")
+                    .help("checks for private items that are missing documentation")
+                    .help_heading("extra warnings")
+                    .action(ArgAction::SetTrue),
             )
             .arg(
-                Arg::new("examples")
-                    .long("examples")
-                    .help_heading("targets")
-                    .conflicts_with_all([
-                        "lib",
-                        "bins",
-                        "bin",
-                        "example",
-                        "tests",
-                        "test",
-                        "benches",
-                        "bench",
-                        "all_targets",
-                    ])
-                    .action(ArgAction::SetTrue)
-                    .help("check all examples"),
-            )
-            .arg(
-                Arg::new("example")
-                    .help_heading("targets")
-                    .conflicts_with_all([
-                        "lib",
-                        "bins",
-                        "bin",
-                        "examples",
-                        "tests",
-                        "test",
-                        "benches",
-                        "bench",
-                        "all_targets",
-                    ])
-                    .long("example")
-                    .help("check only the specified example")
-                    .action(ArgAction::Set),
+                Arg::new("rustdoc")
+                    .long("rustdoc")
+                    .help("also runs rustdoc and reports its documentation lints")
+                    .help_heading("extra warnings")
+                    .action(ArgAction::SetTrue),
             )
             .arg(
-                Arg::new("tests")
-                    .long("tests")
-                    .help_heading("targets")
-                    .conflicts_with_all([
-                        "lib",
-                        "bins",
-                        "bin",
-                        "examples",
-                        "example",
-                        "test",
-                        "benches",
-                        "bench",
-                        "all_targets",
-                    ])
-                    .action(ArgAction::SetTrue)
-                    .help("check all targets that have `test = true` set"),
-            )
-            .arg(
-                Arg::new("test")
-                    .help_heading("targets")
-                    .conflicts_with_all([
-                        "lib",
-                        "bins",
-                        "bin",
-                        "examples",
-                        "example",
-                        "tests",
-                        "benches",
-                        "bench",
-                        "all_targets",
-                    ])
-                    .long("test")
-                    .help("check only the specified test target")
-                    .action(ArgAction::Set),
+                Arg::new("doctests")
+                    .long("doctests")
+                    .help("also runs the doctests and reports the failing and ignored ones")
+                    .help_heading("extra warnings")
+                    .action(ArgAction::SetTrue),
             )
             .arg(
-                Arg::new("benches")
-                    .long("benches")
-                    .help_heading("targets")
-                    .conflicts_with_all([
-                        "lib",
-                        "bins",
-                        "bin",
-                        "examples",
-                        "example",
-                        "tests",
-                        "test",
-                        "bench",
-                        "all_targets",
-                    ])
-                    .action(ArgAction::SetTrue)
-                    .help("check all targets that have `bench = true` set"),
-            )
-            .arg(
-                Arg::new("bench")
-                    .help_heading("targets")
-                    .conflicts_with_all([
-                        "lib",
-                        "bins",
-                        "bin",
-                        "examples",
-                        "example",
-                        "tests",
-                        "test",
-                        "benches",
-                        "all_targets",
-                    ])
-                    .long("bench")
-                    .help("check only the specified bench target"),
-            )
-            .arg(
-                Arg::new("all_targets")
-                    .long("all-targets")
-                    .help_heading("targets")
-                    .conflicts_with_all([
-                        "lib", "bins", "bin", "examples", "example", "tests", "test", "benches",
-                        "bench",
-                    ])
-                    .action(ArgAction::SetTrue)
-                    .help("check all targets"),
+                Arg::new("require_examples")
+                    .long("require-examples")
+                    .help("checks for public functions and types without examples in their docs")
+                    .help_heading("extra warnings")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("placeholders")
+                    .long("placeholders")
+                    .help("checks for public items that only have placeholder docs")
+                    .help_heading("extra warnings")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("audit_allows")
+                    .long("audit-allows")
+                    .help("reports the attributes that hide missing documentation")
+                    .help_heading("extra warnings")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("feature_docs")
+                    .long("feature-docs")
+                    .help("checks that cargo features are described and gated items use `doc(cfg)`")
+                    .help_heading("extra warnings")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("manifest_check")
+                    .long("manifest-check")
+                    .help("checks for package metadata that crates.io shows as documentation")
+                    .help_heading("extra warnings")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("all")
+                    .short('a')
+                    .long("all")
+                    .help("enables all extra warnings")
+                    .help_heading("extra warnings")
+                    .action(ArgAction::SetTrue),
             )
             //
+            .args(target_args())
+            //
+            .args(feature_args())
             .arg(
-                Arg::new("features")
-                    .short('F')
-                    .long("features")
+                Arg::new("each_feature")
+                    .long("each-feature")
                     .help_heading("features")
-                    .conflicts_with_all(["all_features", "no_features"])
-                    .help("space or comma separated list of features to activate")
-                    .action(ArgAction::Append),
+                    .conflicts_with_all(["features", "all_features", "no_features"])
+                    .help("check with no features, then with each feature on its own")
+                    .action(ArgAction::SetTrue),
             )
             .arg(
-                Arg::new("all_features")
-                    .long("all-features")
+                Arg::new("feature_powerset")
+                    .long("feature-powerset")
                     .help_heading("features")
-                    .conflicts_with_all(["features", "no_features"])
-                    .help("activate all available features")
+                    .conflicts_with_all(["features", "all_features", "no_features", "each_feature"])
+                    .help("check every combination of the features")
                     .action(ArgAction::SetTrue),
             )
             .arg(
-                Arg::new("no_features")
-                    .long("no-default-features")
-                    .help_heading("features")
-                    .conflicts_with_all(["all_features", "features"])
-                    .help("do not activate the `default` feature")
+                Arg::new("show_origin")
+                    .long("show-origin")
+                    .help("show the targets each result came from")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("blame")
+                    .long("blame")
+                    .help("show who last changed each item and when, from `git blame`")
                     .action(ArgAction::SetTrue),
             )
             .arg(
@@ -405,6 +586,35 @@ impl Args {
                     .help("tries to make things more compact")
                     .action(ArgAction::SetTrue),
             )
+            .arg(
+                Arg::new("group_by")
+                    .long("group-by")
+                    .help("how the results should be grouped")
+                    .value_parser(clap::value_parser!(GroupBy))
+                    .default_value("file")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("format")
+                    .long("format")
+                    .help("how the results should be shown")
+                    .long_help(
+                        "how the results should be shown. \
+                         html writes a single page with the results to --output",
+                    )
+                    .value_parser(clap::value_parser!(Format))
+                    .default_value("text")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("output")
+                    .long("output")
+                    .short('o')
+                    .value_name("path")
+                    .help("where to write the html report [default: report.html]")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .action(ArgAction::Set),
+            )
             .arg(
                 Arg::new("ignore_config")
                     .long("ignore-config")
@@ -435,14 +645,53 @@ impl Args {
                     .group("filtering")
                     .help_heading("filtering")
                     .help("focus these files")
-                    .long_help("given a glob, only shows the files that match it."),
+                    .long_help(
+                        "given a glob, only shows the files that match it. \
+                         globs starting with `!` hide the files that match them instead.",
+                    ),
+            )
+            .arg(
+                Arg::new("filter_exclude")
+                    .long("filter-exclude")
+                    .action(ArgAction::Append)
+                    .help_heading("filtering")
+                    .help("hide these files")
+                    .long_help("given a glob, hides the files that match it."),
+            )
+            .arg(
+                Arg::new("item")
+                    .long("item")
+                    .action(ArgAction::Append)
+                    .help_heading("filtering")
+                    .help("focus the items with this name or path")
+                    .long_help(
+                        "given a glob, only shows the items whose name or path matches it. \
+                         e.g. `new`, `with_*` or `Mapping::*`",
+                    ),
+            )
+            .arg(
+                Arg::new("item_regex")
+                    .long("item-regex")
+                    .action(ArgAction::Append)
+                    .help_heading("filtering")
+                    .help("focus the items whose name or path matches this regex"),
+            )
+            .arg(
+                Arg::new("lint")
+                    .long("lint")
+                    .action(ArgAction::Append)
+                    .help_heading("filtering")
+                    .help("only show the diagnostics from this lint")
+                    .long_help(
+                        "only show the diagnostics from this lint. \
+                         the tool can be left out, e.g. `missing_errors_doc` or `cds::examples`",
+                    ),
             )
             .arg(
                 Arg::new("include")
                     .short('i')
                     .long("include")
                     .help("include only specific lint kinds")
-                    .conflicts_with("exclude")
                     .help_heading("filtering")
                     .value_parser(clap::value_parser!(ClassifyKind))
                     .action(ArgAction::Append),
@@ -453,9 +702,37 @@ impl Args {
                     .long("exclude")
                     .help("exclude specific lint kinds")
                     .help_heading("filtering")
-                    .conflicts_with("include")
                     .value_parser(clap::value_parser!(ClassifyKind))
                     .action(ArgAction::Append),
+            )
+            .arg(
+                Arg::new("visibility")
+                    .long("visibility")
+                    .help("only show items with this visibility")
+                    .help_heading("filtering")
+                    .value_parser(clap::value_parser!(Visibility))
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("older_than")
+                    .long("older-than")
+                    .value_name("duration")
+                    .help("only show items last changed longer ago than this, like `30d` or `1y`")
+                    .long_help(
+                        "only show items last changed longer ago than this. \
+                         the units are h, d, w, m and y, like `30d` or `1y`. this implies --blame",
+                    )
+                    .help_heading("filtering")
+                    .value_parser(crate::blame::parse_age)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("owner")
+                    .long("owner")
+                    .value_name("team")
+                    .help("only show items in files owned by this team or user in CODEOWNERS")
+                    .help_heading("filtering")
+                    .action(ArgAction::Append),
             );
 
         let mut matches = cmd.get_matches();
@@ -465,14 +742,92 @@ impl Args {
             .unwrap_or_else(|| PathBuf::from("."));
         let path = chorts::locate_manifest(path)?;
 
+        let target_args = cargo_args(
+            &matches,
+            &[
+                ("lib", "--lib"),
+                ("bins", "--bins"),
+                ("examples", "--examples"),
+                ("tests", "--tests"),
+                ("benches", "--benches"),
+                ("all_targets", "--all-targets"),
+            ],
+            &[
+                ("bin", "--bin"),
+                ("example", "--example"),
+                ("test", "--test"),
+                ("bench", "--bench"),
+            ],
+        );
+        let feature_args = cargo_args(
+            &matches,
+            &[
+                ("all_features", "--all-features"),
+                ("no_features", "--no-default-features"),
+            ],
+            &[("features", "--features")],
+        );
+
+        let group_by = matches.remove_one("group_by").unwrap_or_default();
+        let older_than = matches.remove_one("older_than");
+        // grouping and filtering by author or age needs the blame
+        let blame =
+            matches.get_flag("blame") || older_than.is_some() || group_by == GroupBy::Author;
+
+        let command = match matches.remove_subcommand() {
+            Some((name, matches)) if name == "fix" => Some(Subcommand::Fix {
+                yes: matches.get_flag("yes"),
+            }),
+            Some((name, mut matches)) if name == "history" => {
+                let command = match matches.subcommand_name() {
+                    Some("record") => History::Record,
+                    _ => History::Show,
+                };
+                let file = matches
+                    .remove_one("file")
+                    .unwrap_or_else(|| PathBuf::from(".cds-history.jsonl"));
+                Some(Subcommand::History { command, file })
+            }
+            Some((name, mut matches)) if name == "badge" => Some(Subcommand::Badge {
+                output: matches
+                    .remove_one("output")
+                    .unwrap_or_else(|| PathBuf::from("docs.svg")),
+            }),
+            _ => None,
+        };
+
         let mut this = Self {
+            command,
             errors: matches.get_flag("errors"),
             panics: matches.get_flag("panics"),
             safety: matches.get_flag("safety"),
-            target: Target::parse(&mut matches),
-            features: Features::parse(&mut matches),
+            quality: matches.get_flag("quality"),
+            private: matches.get_flag("private"),
+            rustdoc: matches.get_flag("rustdoc"),
+            doctests: matches.get_flag("doctests"),
+            require_examples: matches.get_flag("require_examples"),
+            placeholders: matches.get_flag("placeholders"),
+            audit_allows: matches.get_flag("audit_allows"),
+            feature_docs: matches.get_flag("feature_docs"),
+            manifest_check: matches.get_flag("manifest_check"),
+            target_args,
+            feature_args,
+            feature_matrix: if matches.get_flag("feature_powerset") {
+                Some(FeatureMatrix::Powerset)
+            } else if matches.get_flag("each_feature") {
+                Some(FeatureMatrix::Each)
+            } else {
+                None
+            },
             nightly: matches.get_flag("nightly"),
+            override_lints: matches.get_flag("override_lints"),
             compact: matches.get_flag("compact"),
+            show_origin: matches.get_flag("show_origin"),
+            blame,
+            older_than,
+            group_by,
+            format: matches.remove_one("format").unwrap_or_default(),
+            output: matches.remove_one("output"),
 
             show_item: matches.get_flag("show_item"),
 
@@ -492,14 +847,24 @@ impl Args {
                 .flatten()
                 .collect(),
 
-            filter: glob_filters(
-                {
-                    let mut parent = path.clone();
-                    parent.pop();
-                    parent
-                },
+            visibility: matches.remove_one("visibility"),
+
+            filter: FileFilter::new(
                 matches.remove_many::<String>("glob").into_iter().flatten(),
+                matches
+                    .remove_many::<String>("filter_exclude")
+                    .into_iter()
+                    .flatten(),
             )?,
+            items: ItemFilter::new(
+                matches.remove_many::<String>("item").into_iter().flatten(),
+                matches
+                    .remove_many::<String>("item_regex")
+                    .into_iter()
+                    .flatten(),
+            )?,
+            lints: matches.remove_many("lint").into_iter().flatten().collect(),
+            owners: matches.remove_many("owner").into_iter().flatten().collect(),
             path,
         };
 
@@ -507,31 +872,244 @@ impl Args {
             this.errors = true;
             this.panics = true;
             this.safety = true;
+            this.quality = true;
         }
 
         Ok(this)
     }
 }
 
-fn glob_filters(
-    root: PathBuf,
-    patterns: impl IntoIterator<Item = String>,
-) -> anyhow::Result<Vec<PathBuf>> {
-    let mut paths = vec![];
-
-    for pattern in patterns {
-        let new = root.join(pattern);
-        for pat in glob::glob(&new.to_string_lossy())
-            .with_context(|| anyhow::anyhow!("invalid glob: {}", new.display()))?
-        {
-            let pat = pat?;
-            let local = pat.strip_prefix(&root).unwrap_or(&pat);
-            if local.starts_with("target") {
-                continue;
-            }
-            paths.push(local.to_path_buf());
+fn target_args() -> [Arg; 10] {
+    [
+        Arg::new("lib")
+            .long("lib")
+            .help_heading("targets")
+            .conflicts_with_all([
+                "bins",
+                "bin",
+                "examples",
+                "example",
+                "tests",
+                "test",
+                "benches",
+                "bench",
+                "all_targets",
+            ])
+            .action(ArgAction::SetTrue)
+            .help("check only this package's library"),
+        Arg::new("bins")
+            .long("bins")
+            .help_heading("targets")
+            .conflicts_with_all([
+                "lib",
+                "bin",
+                "examples",
+                "example",
+                "tests",
+                "test",
+                "benches",
+                "bench",
+                "all_targets",
+            ])
+            .action(ArgAction::SetTrue)
+            .help("check all binaries"),
+        Arg::new("bin")
+            .help_heading("targets")
+            .conflicts_with_all([
+                "lib",
+                "bins",
+                "examples",
+                "example",
+                "tests",
+                "test",
+                "benches",
+                "bench",
+                "all_targets",
+            ])
+            .long("bin")
+            .help("check only the specified binary")
+            .action(ArgAction::Set),
+        Arg::new("examples")
+            .long("examples")
+            .help_heading("targets")
+            .conflicts_with_all([
+                "lib",
+                "bins",
+                "bin",
+                "example",
+                "tests",
+                "test",
+                "benches",
+                "bench",
+                "all_targets",
+            ])
+            .action(ArgAction::SetTrue)
+            .help("check all examples"),
+        Arg::new("example")
+            .help_heading("targets")
+            .conflicts_with_all([
+                "lib",
+                "bins",
+                "bin",
+                "examples",
+                "tests",
+                "test",
+                "benches",
+                "bench",
+                "all_targets",
+            ])
+            .long("example")
+            .help("check only the specified example")
+            .action(ArgAction::Set),
+        Arg::new("tests")
+            .long("tests")
+            .help_heading("targets")
+            .conflicts_with_all([
+                "lib",
+                "bins",
+                "bin",
+                "examples",
+                "example",
+                "test",
+                "benches",
+                "bench",
+                "all_targets",
+            ])
+            .action(ArgAction::SetTrue)
+            .help("check all targets that have `test = true` set"),
+        Arg::new("test")
+            .help_heading("targets")
+            .conflicts_with_all([
+                "lib",
+                "bins",
+                "bin",
+                "examples",
+                "example",
+                "tests",
+                "benches",
+                "bench",
+                "all_targets",
+            ])
+            .long("test")
+            .help("check only the specified test target")
+            .action(ArgAction::Set),
+        Arg::new("benches")
+            .long("benches")
+            .help_heading("targets")
+            .conflicts_with_all([
+                "lib",
+                "bins",
+                "bin",
+                "examples",
+                "example",
+                "tests",
+                "test",
+                "bench",
+                "all_targets",
+            ])
+            .action(ArgAction::SetTrue)
+            .help("check all targets that have `bench = true` set"),
+        Arg::new("bench")
+            .help_heading("targets")
+            .conflicts_with_all([
+                "lib",
+                "bins",
+                "bin",
+                "examples",
+                "example",
+                "tests",
+                "test",
+                "benches",
+                "all_targets",
+            ])
+            .long("bench")
+            .help("check only the specified bench target"),
+        Arg::new("all_targets")
+            .long("all-targets")
+            .help_heading("targets")
+            .conflicts_with_all([
+                "lib", "bins", "bin", "examples", "example", "tests", "test", "benches", "bench",
+            ])
+            .action(ArgAction::SetTrue)
+            .help("check all targets"),
+    ]
+}
+
+fn feature_args() -> [Arg; 3] {
+    [
+        Arg::new("features")
+            .short('F')
+            .long("features")
+            .help_heading("features")
+            .conflicts_with_all(["all_features", "no_features"])
+            .help("space or comma separated list of features to activate")
+            .action(ArgAction::Append),
+        Arg::new("all_features")
+            .long("all-features")
+            .help_heading("features")
+            .conflicts_with_all(["features", "no_features"])
+            .help("activate all available features")
+            .action(ArgAction::SetTrue),
+        Arg::new("no_features")
+            .long("no-default-features")
+            .help_heading("features")
+            .conflicts_with_all(["all_features", "features"])
+            .help("do not activate the `default` feature")
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+fn cargo_args(
+    matches: &clap::ArgMatches,
+    flags: &[(&str, &str)],
+    values: &[(&str, &str)],
+) -> Vec<String> {
+    let mut args = vec![];
+
+    for &(id, flag) in flags {
+        if matches.get_flag(id) {
+            args.push(flag.to_string());
+        }
+    }
+
+    for &(id, flag) in values {
+        for value in matches.get_many::<String>(id).into_iter().flatten() {
+            args.extend([flag.to_string(), value.clone()]);
         }
     }
 
-    Ok(paths)
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify() {
+        let kind = |input| ClassifyKind::parse(input).map(|(kind, _)| kind);
+        assert_eq!(
+            kind("missing documentation for a foreign function"),
+            Some(ClassifyKind::ForeignFunction)
+        );
+        assert_eq!(
+            kind("missing documentation for a function"),
+            Some(ClassifyKind::Function)
+        );
+        assert_eq!(
+            kind("the function `run` is missing `# Panics`, a required section"),
+            Some(ClassifyKind::Section)
+        );
+        assert_eq!(
+            kind("you should put bare URLs between `<`/`>` or make a proper Markdown link"),
+            Some(ClassifyKind::Markdown)
+        );
+        for clippy in [
+            "docs for function returning `Result` missing `# Errors` section",
+            "docs for function which may panic missing `# Panics` section",
+            "unsafe function's docs are missing a `# Safety` section",
+        ] {
+            assert_eq!(kind(clippy), None, "{clippy}");
+        }
+    }
 }
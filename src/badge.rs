@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use anyhow::Context as _;
+
+use crate::{args::Visibility, config::Badge, html::escape, source, visit::MissingDocs};
+
+// roughly the width of a character of 11px verdana
+const CHAR_WIDTH: usize = 7;
+const PADDING: usize = 10;
+
+/// writes a badge with the percentage of documented items to `output`
+///
+/// the undocumented items are the `missing_docs` results in `docs`, so the badge agrees with the report,
+/// and the documented ones are found by scanning the crates. private items are only counted with `private`
+pub fn write(
+    manifest_path: &Path,
+    output: &Path,
+    private: bool,
+    docs: &MissingDocs,
+    badge: &Badge,
+) -> anyhow::Result<()> {
+    let files = source::scan(manifest_path)?
+        .into_iter()
+        .filter(|file| docs.is_focused(&docs.package_path(&file.path)))
+        .collect::<Vec<_>>();
+
+    // `missing_docs` reports the crate itself too
+    let crates = files
+        .iter()
+        .filter(|file| file.root && !file.docs.is_empty())
+        .count();
+    let documented = crates
+        + files
+            .iter()
+            .flat_map(|file| &file.items)
+            .filter(|item| private || item.visibility == Visibility::Public)
+            .filter(|item| item.is_documented())
+            .count();
+
+    let undocumented = docs
+        .map
+        .values()
+        .flatten()
+        .filter(|missing| match &*missing.code {
+            "missing_docs" => true,
+            "clippy::missing_docs_in_private_items" => private,
+            _ => false,
+        })
+        .count();
+
+    let total = documented + undocumented;
+    let coverage = coverage(documented, total);
+
+    let svg = svg(
+        &badge.label,
+        &format!("{coverage}%"),
+        &badge.color(coverage).to_string(),
+    );
+    std::fs::write(output, svg)
+        .with_context(|| anyhow::anyhow!("cannot write {path}", path = output.display()))?;
+
+    println!(
+        "wrote {path} ({documented} of {total} items are documented)",
+        path = output.display()
+    );
+    Ok(())
+}
+
+// an empty crate is fully documented
+fn coverage(documented: usize, total: usize) -> u32 {
+    match total {
+        0 => 100,
+        total => (documented * 100 / total) as u32,
+    }
+}
+
+// the same layout as the flat badges from shields.io, without anything external
+fn svg(label: &str, value: &str, color: &str) -> String {
+    let (label, value) = (escape(label), escape(value));
+    let label_width = label.chars().count() * CHAR_WIDTH + PADDING;
+    let value_width = value.chars().count() * CHAR_WIDTH + PADDING;
+    let width = label_width + value_width;
+    let (label_x, value_x) = (label_width / 2, label_width + value_width / 2);
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {value}">
+  <title>{label}: {value}</title>
+  <linearGradient id="s" x2="0" y2="100%">
+    <stop offset="0" stop-color="#bbb" stop-opacity=".1"/>
+    <stop offset="1" stop-opacity=".1"/>
+  </linearGradient>
+  <clipPath id="r">
+    <rect width="{width}" height="20" rx="3" fill="#fff"/>
+  </clipPath>
+  <g clip-path="url(#r)">
+    <rect width="{label_width}" height="20" fill="#555"/>
+    <rect x="{label_width}" width="{value_width}" height="20" fill="{color}"/>
+    <rect width="{width}" height="20" fill="url(#s)"/>
+  </g>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+    <text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text>
+    <text x="{label_x}" y="14">{label}</text>
+    <text x="{value_x}" y="15" fill="#010101" fill-opacity=".3">{value}</text>
+    <text x="{value_x}" y="14">{value}</text>
+  </g>
+</svg>
+"##
+    )
+}
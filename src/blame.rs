@@ -0,0 +1,146 @@
+use std::{
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context as _;
+
+use crate::visit::MissingDocs;
+
+/// who last changed a line, from `git blame`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blame {
+    pub author: String,
+    // seconds since the unix epoch
+    pub time: u64,
+}
+
+impl Blame {
+    /// the commit date, like `2024-03-01`
+    pub fn date(&self) -> String {
+        date(self.time)
+    }
+
+    pub fn is_older_than(&self, age: Duration) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        now.saturating_sub(Duration::from_secs(self.time)) > age
+    }
+}
+
+/// formats seconds since the unix epoch as a date, like `2024-03-01`
+pub fn date(time: u64) -> String {
+    let (year, month, day) = civil_from_days((time / 86_400) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// attaches the author and commit date of each row from the local repository
+///
+/// files that aren't tracked by git are left without a blame
+pub fn annotate(docs: &mut MissingDocs) -> anyhow::Result<()> {
+    let files = docs
+        .map
+        .keys()
+        .filter_map(|file| Some((file.clone(), docs.locate(file)?)))
+        .collect::<Vec<_>>();
+
+    for (file, path) in files {
+        let Some(lines) = blame(&path)? else {
+            continue;
+        };
+        for missing in docs.map.get_mut(&file).into_iter().flatten() {
+            missing.blame = lines.get(missing.message.row.wrapping_sub(1)).cloned();
+        }
+    }
+
+    Ok(())
+}
+
+/// only keeps the results last changed longer ago than `age`
+pub fn retain_older(docs: &mut MissingDocs, age: Duration) {
+    for list in docs.map.values_mut() {
+        list.retain(|missing| {
+            missing
+                .blame
+                .as_ref()
+                .is_some_and(|blame| blame.is_older_than(age))
+        });
+    }
+    docs.map.retain(|_, list| !list.is_empty());
+}
+
+/// parses an age like `30d`, `6w`, `3m` or `1y`
+pub fn parse_age(input: &str) -> Result<Duration, String> {
+    const DAY: u64 = 86_400;
+    let (num, unit) = input.split_at(
+        input
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(input.len()),
+    );
+    let num = num
+        .parse::<u64>()
+        .map_err(|_| format!("invalid duration: {input}"))?;
+    let secs = match unit {
+        "h" => 3_600,
+        "d" | "" => DAY,
+        "w" => 7 * DAY,
+        "m" => 30 * DAY,
+        "y" => 365 * DAY,
+        _ => return Err(format!("unknown unit in {input}, expected h, d, w, m or y")),
+    };
+    num.checked_mul(secs)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("the duration {input} is too long"))
+}
+
+// the blame of every line in the file, or nothing if git doesn't know the file
+fn blame(path: &Path) -> anyhow::Result<Option<Vec<Blame>>> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Ok(None);
+    };
+
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["blame", "--line-porcelain", "--"])
+        .arg(name)
+        .output()
+        .with_context(|| anyhow::anyhow!("cannot run git blame"))?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    let mut lines = vec![];
+    let (mut author, mut time) = (String::new(), 0);
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(name) = line.strip_prefix("author ") {
+            author = name.to_string();
+        } else if let Some(t) = line.strip_prefix("author-time ") {
+            time = t.parse().unwrap_or_default();
+        } else if line.starts_with('\t') {
+            // the content of the line ends the headers for it
+            lines.push(Blame {
+                author: std::mem::take(&mut author),
+                time,
+            });
+        }
+    }
+
+    Ok(Some(lines))
+}
+
+// from https://howardhinnant.github.io/date_algorithms.html
+const fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
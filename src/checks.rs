@@ -0,0 +1,708 @@
+use std::path::Path;
+
+use crate::{
+    args::{ClassifyKind, Visibility},
+    config::{Features, Placeholders, Rule},
+    source::{Attribute, Item, SourceFile},
+    visit::{MissingDocs, Snippet, Spanned},
+};
+
+/// reports documented public functions and types that don't have any examples
+pub fn examples(files: &[SourceFile], docs: &mut MissingDocs) {
+    for file in files {
+        for item in &file.items {
+            if !needs_examples(item) || has_examples(&item.docs) {
+                continue;
+            }
+
+            docs.report(
+                &docs.package_path(&file.path),
+                "cds::examples",
+                Spanned::new(
+                    format!("the {} has no examples", item.describe()),
+                    item.row,
+                    item.col,
+                ),
+                vec![item.snippet()],
+                None,
+            );
+        }
+    }
+}
+
+/// reports documented public items that are missing a section required by the configured rules
+pub fn sections(files: &[SourceFile], rules: &[(ClassifyKind, &Rule)], docs: &mut MissingDocs) {
+    for file in files {
+        for item in &file.items {
+            if item.visibility != Visibility::Public || !item.is_documented() {
+                continue;
+            }
+
+            let required = rules
+                .iter()
+                .filter(|(kind, _)| *kind == item.kind)
+                .flat_map(|(_, rule)| rule.required(item));
+
+            for section in required {
+                if item.has_section(section) {
+                    continue;
+                }
+
+                docs.report(
+                    &docs.package_path(&file.path),
+                    "cds::sections",
+                    Spanned::new(
+                        format!(
+                            "the {} is missing `# {section}`, a required section",
+                            item.describe()
+                        ),
+                        item.row,
+                        item.col,
+                    ),
+                    vec![item.snippet()],
+                    None,
+                );
+            }
+        }
+    }
+}
+
+/// reports public items whose docs are only a placeholder
+pub fn placeholders(files: &[SourceFile], placeholders: &Placeholders, docs: &mut MissingDocs) {
+    for file in files {
+        for item in &file.items {
+            if item.visibility != Visibility::Public || !item.is_documented() {
+                continue;
+            }
+
+            if !is_placeholder(item, placeholders) {
+                continue;
+            }
+
+            docs.report(
+                &docs.package_path(&file.path),
+                "cds::placeholders",
+                Spanned::new(
+                    format!("the {} has placeholder docs", item.describe()),
+                    item.row,
+                    item.col,
+                ),
+                vec![item.snippet()],
+                None,
+            );
+        }
+    }
+}
+
+fn is_placeholder(item: &Item, placeholders: &Placeholders) -> bool {
+    let mut in_code = false;
+    let lines = item
+        .docs
+        .iter()
+        .filter(|line| {
+            if line.trim_start().starts_with("```") {
+                in_code = !in_code;
+                return false;
+            }
+            !in_code
+        })
+        .map(|line| line.trim())
+        .collect::<Vec<_>>();
+    let text = lines.join(" ");
+    let text = text.trim_start();
+
+    let words = text
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_'))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+
+    if words.len() < placeholders.min_words {
+        return true;
+    }
+
+    let is_marker = |word: &str| {
+        placeholders
+            .patterns
+            .iter()
+            .any(|pattern| word.eq_ignore_ascii_case(pattern))
+    };
+
+    // `TODO: document this` and `FIXME(someone) - ..`, but not `TODO items are returned in order`
+    let leading = placeholders.patterns.iter().any(|pattern| {
+        let rest = text
+            .get(..pattern.len())
+            .filter(|head| head.eq_ignore_ascii_case(pattern))
+            .map(|_| text[pattern.len()..].trim_start());
+        rest.is_some_and(|rest| rest.is_empty() || rest.starts_with([':', '-', '!', '(']))
+    });
+
+    // `Lorem ipsum dolor sit amet`, which is filler wherever it is
+    let filler = is_marker("lorem") && text.to_ascii_lowercase().contains("lorem ipsum");
+
+    // `TODO docs`, where the markers are most of the words
+    let markers = words.iter().filter(|word| is_marker(word)).count();
+    if leading || filler || markers * 2 >= words.len() {
+        return true;
+    }
+
+    // `fn new_thing` with `/// New thing.`
+    let restated = words.join(" ").to_ascii_lowercase();
+    restated == split_name(&item.name)
+}
+
+// `FooBar` and `foo_bar` both become `foo bar`
+fn split_name(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.trim_matches('_').char_indices() {
+        if c == '_' {
+            out.push(' ');
+            continue;
+        }
+        if c.is_uppercase() && i > 0 && !out.ends_with(' ') {
+            out.push(' ');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+/// reports the attributes that hide missing documentation, and how many undocumented public items they cover
+pub fn allows(files: &[SourceFile], docs: &mut MissingDocs) {
+    for file in files {
+        for attribute in &file.attributes {
+            if !suppresses_docs(&attribute.text) {
+                continue;
+            }
+
+            let count = count_hidden(files, file, attribute);
+
+            let plural = if count == 1 { "" } else { "s" };
+            docs.report(
+                &docs.package_path(&file.path),
+                "cds::allows",
+                Spanned::new(
+                    format!(
+                        "`{}` hides {count} undocumented public item{plural}",
+                        attribute.text
+                    ),
+                    attribute.row,
+                    attribute.col,
+                ),
+                vec![attribute.snippet()],
+                None,
+            );
+        }
+    }
+}
+
+/// reports the features in the manifest that have no description
+///
+/// features are described by `##` comments above them, like `document-features` uses,
+/// or by the configured table in the manifest
+pub fn features(
+    manifest_path: &Path,
+    features: &Features,
+    docs: &mut MissingDocs,
+) -> anyhow::Result<()> {
+    let source = std::fs::read_to_string(manifest_path)?;
+    let manifest = crate::manifest::read(manifest_path)?;
+
+    let table = features
+        .table
+        .split('.')
+        .try_fold(&manifest, |table, key| table.get(key)?.as_table());
+    let described = |name: &str| {
+        table
+            .and_then(|table| table.get(name))
+            .and_then(toml::Value::as_str)
+            .is_some_and(|desc| !desc.trim().is_empty())
+    };
+
+    let mut in_features = false;
+    let mut comments = false;
+    for (i, line) in source.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_features = trimmed == "[features]";
+            comments = false;
+            continue;
+        }
+        if !in_features {
+            continue;
+        }
+
+        if let Some(comment) = trimmed.strip_prefix("##") {
+            comments |= !comment.trim().is_empty();
+            continue;
+        }
+
+        let Some((name, _)) = trimmed.split_once('=') else {
+            comments = false;
+            continue;
+        };
+
+        let name = name.trim().trim_matches('"');
+        // `default` is a list of other features, not a feature of its own
+        if name != "default" && !comments && !described(name) {
+            report_manifest(
+                docs,
+                format!("the feature `{name}` has no description"),
+                i + 1,
+                line,
+            );
+        }
+        comments = false;
+    }
+
+    Ok(())
+}
+
+// the fields crates.io and docs.rs show for a package
+const METADATA: &[&str] = &[
+    "description",
+    "documentation",
+    "readme",
+    "repository",
+    "keywords",
+    "categories",
+];
+
+/// reports missing or empty package metadata, and a readme that isn't in the crate docs
+pub fn manifest(manifest_path: &Path, docs: &mut MissingDocs) -> anyhow::Result<()> {
+    let source = std::fs::read_to_string(manifest_path)?;
+    let manifest = crate::manifest::read(manifest_path)?;
+    // a virtual manifest has nothing to publish
+    let Some(package) = manifest.get("package").and_then(toml::Value::as_table) else {
+        return Ok(());
+    };
+
+    let workspace = crate::manifest::workspace(manifest_path)?;
+    let inherited = |field: &str| {
+        workspace
+            .as_ref()
+            .and_then(|(_, manifest)| manifest.get("workspace")?.get("package")?.get(field))
+            .cloned()
+    };
+
+    let dir = manifest_path.parent().unwrap_or(Path::new("."));
+    // cargo uses these when `readme` isn't set
+    let default_readme = ["README.md", "README.txt", "README"]
+        .into_iter()
+        .find(|name| dir.join(name).is_file());
+
+    let header = manifest_line(&source, "package", None);
+    for &field in METADATA {
+        let value = match package.get(field) {
+            Some(value) if value.get("workspace").and_then(toml::Value::as_bool) == Some(true) => {
+                inherited(field)
+            }
+            Some(value) => Some(value.clone()),
+            None if field == "readme" => default_readme.map(toml::Value::from),
+            None => None,
+        };
+
+        let state = match value {
+            None | Some(toml::Value::Boolean(false)) => "missing",
+            Some(toml::Value::String(s)) if s.trim().is_empty() => "empty",
+            Some(toml::Value::Array(list)) if list.is_empty() => "empty",
+            Some(..) => continue,
+        };
+
+        let Some((row, line)) = manifest_line(&source, "package", Some(field)).or(header) else {
+            continue;
+        };
+        report_manifest(
+            docs,
+            format!("the `{field}` metadata is {state}"),
+            row,
+            line,
+        );
+    }
+
+    let readme = match package.get("readme") {
+        Some(toml::Value::String(readme)) => Some(&**readme),
+        Some(..) => None,
+        None => default_readme,
+    };
+    let lib = dir.join("src").join("lib.rs");
+    if let (Some(readme), Ok(lib_source)) = (readme, std::fs::read_to_string(&lib)) {
+        let (_, attributes, _) = crate::source::parse(&lib_source);
+        let name = Path::new(readme).file_name().and_then(|s| s.to_str());
+        let included = attributes.iter().any(|attr| {
+            attr.inner
+                && attr.text.contains("include_str!")
+                && name.is_some_and(|name| attr.text.contains(name))
+        });
+
+        let line = lib_source.lines().next().unwrap_or_default();
+        if !included {
+            docs.report(
+                &docs.package_path(Path::new("src/lib.rs")),
+                "cds::readme",
+                Spanned::new(
+                    format!("the readme `{readme}` isn't included in the crate docs"),
+                    1,
+                    1,
+                ),
+                vec![Snippet {
+                    data: line.to_string(),
+                    start: 1,
+                    end: line.len() + 1,
+                }],
+                None,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// the row and line of `key` in `[section]`, or of the section header without a key
+fn manifest_line<'a>(
+    source: &'a str,
+    section: &str,
+    key: Option<&str>,
+) -> Option<(usize, &'a str)> {
+    let header = format!("[{section}]");
+    let mut in_section = false;
+    for (i, line) in source.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_section = trimmed == header;
+            if in_section && key.is_none() {
+                return Some((i + 1, line));
+            }
+            continue;
+        }
+
+        let Some(key) = key.filter(|_| in_section) else {
+            continue;
+        };
+        // `description = ..` and `description.workspace = true`
+        let name = trimmed.split(['=', '.']).next().unwrap_or_default().trim();
+        if name == key {
+            return Some((i + 1, line));
+        }
+    }
+    None
+}
+
+fn report_manifest(docs: &mut MissingDocs, message: String, row: usize, line: &str) {
+    let col = line.len() - line.trim_start().len() + 1;
+    docs.report(
+        &docs.package_path(Path::new("Cargo.toml")),
+        "cds::manifest",
+        Spanned::new(message, row, col),
+        vec![Snippet {
+            data: line.to_string(),
+            start: col,
+            end: line.len() + 1,
+        }],
+        None,
+    );
+}
+
+/// reports public items gated on a feature that don't show it with `doc(cfg)`
+pub fn doc_cfg(files: &[SourceFile], docs: &mut MissingDocs) {
+    // rustdoc adds the `doc(cfg)` on its own with `doc_auto_cfg`
+    let auto = files.iter().any(|file| {
+        file.root
+            && file
+                .attributes
+                .iter()
+                .any(|attr| attr.inner && attr.text.contains("auto_cfg"))
+    });
+    if auto {
+        return;
+    }
+
+    for file in files {
+        for attribute in &file.attributes {
+            if attribute.inner || !is_feature_gate(&attribute.text) {
+                continue;
+            }
+
+            let Some(item) = file
+                .items
+                .iter()
+                .find(|item| item.row == attribute.start)
+                .filter(|item| item.visibility == Visibility::Public)
+            else {
+                continue;
+            };
+
+            // the `doc(cfg)` is next to the `cfg` on the same item
+            let features = gated_features(&attribute.text);
+            let shown = file.attributes.iter().any(|other| {
+                !other.inner
+                    && other.start == attribute.start
+                    && other.text.contains("doc(cfg(")
+                    && features.iter().all(|feature| other.text.contains(feature))
+            });
+            if shown {
+                continue;
+            }
+
+            let features = features
+                .iter()
+                .map(|feature| format!("`{feature}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            docs.report(
+                &docs.package_path(&file.path),
+                "cds::doc_cfg",
+                Spanned::new(
+                    format!(
+                        "the {} is gated on {features} without `doc(cfg)`",
+                        item.describe()
+                    ),
+                    attribute.row,
+                    attribute.col,
+                ),
+                vec![attribute.snippet()],
+                None,
+            );
+        }
+    }
+}
+
+// `#[cfg(feature = "foo")]`, but not `#[cfg_attr(feature = "foo", ..)]`
+fn is_feature_gate(attr: &str) -> bool {
+    let attr = compact(attr);
+    attr.starts_with("#[cfg(") && attr.contains("feature=")
+}
+
+// the names in each `feature = "name"` of the attribute
+fn gated_features(attr: &str) -> Vec<String> {
+    compact(attr)
+        .split("feature=\"")
+        .skip(1)
+        .filter_map(|s| s.split_once('"'))
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+fn compact(attr: &str) -> String {
+    attr.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+// this also handles `cfg_attr(.., allow(missing_docs))`
+fn suppresses_docs(attr: &str) -> bool {
+    let attr = compact(attr);
+
+    let allowed =
+        (attr.contains("allow(") || attr.contains("expect(")) && attr.contains("missing_docs");
+    allowed || attr.contains("doc(hidden)")
+}
+
+// the undocumented public items the attribute covers in `file`,
+// and in every file of the modules it covers, all the way down
+fn count_hidden(files: &[SourceFile], file: &SourceFile, attribute: &Attribute) -> usize {
+    let undocumented =
+        |item: &&Item| item.visibility == Visibility::Public && !item.is_documented();
+
+    let mut count = file
+        .items
+        .iter()
+        .filter(|item| attribute.covers(item.row))
+        .filter(undocumented)
+        .count();
+
+    let mut stack = file
+        .modules
+        .iter()
+        .filter(|&&(item, _)| attribute.covers(file.items[item].row))
+        .map(|&(_, index)| index)
+        .collect::<Vec<_>>();
+    let mut seen = vec![];
+    while let Some(index) = stack.pop() {
+        if seen.contains(&index) {
+            continue;
+        }
+        seen.push(index);
+        count += files[index].items.iter().filter(undocumented).count();
+        stack.extend(files[index].modules.iter().map(|&(_, index)| index));
+    }
+    count
+}
+
+fn needs_examples(item: &Item) -> bool {
+    use ClassifyKind as K;
+    item.visibility == Visibility::Public
+        && item.is_documented()
+        && matches!(
+            item.kind,
+            K::Function
+                | K::Method
+                | K::AssociatedFunction
+                | K::Struct
+                | K::Union
+                | K::Enum
+                | K::Trait
+                | K::TypeAlias
+        )
+}
+
+fn has_examples(docs: &[String]) -> bool {
+    docs.iter().map(|s| s.trim()).any(|line| {
+        line.starts_with("```")
+            || line
+                .strip_prefix('#')
+                .map(|s| s.trim_start_matches('#').trim().to_ascii_lowercase())
+                .is_some_and(|s| s == "example" || s == "examples")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visit::FileFilter;
+
+    fn file(path: &str, source: &str) -> SourceFile {
+        let (items, attributes, docs) = crate::source::parse(source);
+        SourceFile {
+            path: path.into(),
+            items,
+            attributes,
+            docs,
+            root: path == "src/lib.rs",
+            modules: vec![],
+        }
+    }
+
+    fn messages(docs: &MissingDocs) -> Vec<&str> {
+        docs.map
+            .values()
+            .flatten()
+            .map(|missing| &*missing.message.item)
+            .collect()
+    }
+
+    fn documented(docs: &str) -> Item {
+        let source = docs
+            .lines()
+            .map(|line| format!("/// {line}\n"))
+            .collect::<String>();
+        let (items, _, _) = crate::source::parse(&format!("{source}pub fn new_thing() {{}}"));
+        items.into_iter().next().unwrap()
+    }
+
+    fn missing_docs() -> MissingDocs {
+        MissingDocs::new(FileFilter::default(), vec![], vec![], None)
+    }
+
+    #[test]
+    fn allows_modules() {
+        let mut files = [
+            file(
+                "src/lib.rs",
+                r#"#![allow(missing_docs)]
+/// Documented.
+pub fn documented() {}
+pub fn undocumented() {}
+/// Shared things.
+pub mod shared;
+"#,
+            ),
+            file(
+                "src/shared.rs",
+                r#"pub fn shared() {}
+/// Documented.
+pub fn documented() {}
+/// Hidden things.
+#[allow(missing_docs)]
+pub mod foo;
+"#,
+            ),
+            file(
+                "src/shared/foo.rs",
+                r#"pub fn foo() {}
+/// Bar things.
+pub mod bar;
+"#,
+            ),
+            file("src/shared/foo/bar.rs", "pub fn bar() {}\n"),
+        ];
+        // the `mod foo;` items and their files, which `scan` finds on disk
+        for (parent, index) in [(0, 1), (1, 2), (2, 3)] {
+            let item = files[parent]
+                .items
+                .iter()
+                .position(|item| item.kind == ClassifyKind::Module)
+                .unwrap();
+            files[parent].modules.push((item, index));
+        }
+
+        let mut docs = missing_docs();
+        allows(&files, &mut docs);
+        assert_eq!(
+            messages(&docs),
+            [
+                "`#![allow(missing_docs)]` hides 4 undocumented public items",
+                "`#[allow(missing_docs)]` hides 2 undocumented public items",
+            ]
+        );
+    }
+
+    #[test]
+    fn doc_cfg_modules() {
+        let files = [file(
+            "src/lib.rs",
+            r#"
+#[cfg(feature = "foo")]
+pub mod foo;
+
+#[cfg(feature = "bar")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bar")))]
+pub mod bar;
+
+#[cfg(feature = "baz")]
+mod baz;
+
+#[cfg(feature = "qux")]
+pub fn qux() {}
+"#,
+        )];
+
+        let mut docs = missing_docs();
+        doc_cfg(&files, &mut docs);
+        assert_eq!(
+            messages(&docs),
+            [
+                "the module `foo` is gated on `foo` without `doc(cfg)`",
+                "the function `qux` is gated on `qux` without `doc(cfg)`",
+            ]
+        );
+    }
+
+    #[test]
+    fn placeholders() {
+        let placeholders = Placeholders::default();
+        for docs in [
+            "TODO",
+            "TODO: document this",
+            "todo - write the docs for this function",
+            "FIXME(someone): explain the arguments",
+            "XXX!",
+            "TODO docs",
+            "New thing.",
+            "Thing",
+            "```\nlet x = 1;\n```",
+            "Lorem ipsum dolor sit amet, consectetur adipiscing elit.",
+            "Lorem ipsum generator that produces filler text for the tests.",
+        ] {
+            assert!(is_placeholder(&documented(docs), &placeholders), "{docs}");
+        }
+
+        for docs in [
+            "Returns the TODO items that are still open for this user.",
+            "Generates lorem text for the fixtures of the tests.",
+            "TODOs are parsed from the comments in the file.",
+            "Makes a new thing.",
+            "Makes a thing.\n\nTODO: document the options",
+        ] {
+            assert!(!is_placeholder(&documented(docs), &placeholders), "{docs}");
+        }
+    }
+}
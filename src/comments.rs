@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+
+use crate::visit::MissingDocs;
+
+const HINT: &str = "did you mean `///`?";
+
+/// a regular comment directly above an item
+struct Comment {
+    // 0-based, inclusive
+    start: usize,
+    end: usize,
+    block: bool,
+}
+
+impl Comment {
+    fn find(lines: &[&str], row: usize) -> Option<Self> {
+        // skip the attributes between the comment and the item
+        let mut end = row.checked_sub(2)?;
+        while lines.get(end)?.trim_start().starts_with("#[") {
+            end = end.checked_sub(1)?;
+        }
+
+        let is_line = |line: &str| {
+            let line = line.trim_start();
+            line.starts_with("//") && !line.starts_with("///") && !line.starts_with("//!")
+        };
+
+        if is_line(lines[end]) {
+            let mut start = end;
+            while start > 0 && is_line(lines[start - 1]) {
+                start -= 1;
+            }
+            let text = lines[start..=end]
+                .iter()
+                .map(|line| line.trim_start().trim_start_matches('/'));
+            return Self::is_prose(text).then_some(Self {
+                start,
+                end,
+                block: false,
+            });
+        }
+
+        if !lines[end].trim_end().ends_with("*/") {
+            return None;
+        }
+
+        let start = (0..=end).rev().find(|&i| lines[i].contains("/*"))?;
+        let open = lines[start].trim_start();
+        if !open.starts_with("/*") || open.starts_with("/**") || open.starts_with("/*!") {
+            return None;
+        }
+
+        let text = lines[start..=end].iter().map(|line| {
+            let line = line.trim();
+            let line = line.strip_prefix("/*").unwrap_or(line);
+            let line = line.strip_suffix("*/").unwrap_or(line);
+            line.trim_start_matches('*')
+        });
+        Self::is_prose(text).then_some(Self {
+            start,
+            end,
+            block: true,
+        })
+    }
+
+    // notes, license headers and commented out code aren't docs
+    fn is_prose<'a>(mut lines: impl Iterator<Item = &'a str>) -> bool {
+        lines.all(|line| {
+            let line = line.trim();
+            let upper = line.to_ascii_uppercase();
+            let is_note = ["SAFETY:", "NOTE:", "COPYRIGHT", "SPDX-", "(C)", "©"]
+                .iter()
+                .any(|marker| upper.starts_with(marker) || upper.contains(&format!(" {marker}")));
+            let is_code = line.ends_with([';', '{', '}'])
+                || ["let ", "fn ", "use ", "pub ", "impl ", "mod ", "#[", "//"]
+                    .iter()
+                    .any(|start| line.starts_with(start));
+            !is_note && !is_code
+        })
+    }
+}
+
+fn is_undocumented(code: &str) -> bool {
+    matches!(
+        code,
+        "missing_docs" | "clippy::missing_docs_in_private_items"
+    )
+}
+
+/// marks the undocumented items that have a regular comment above them
+pub fn annotate(docs: &mut MissingDocs) {
+    let sources = docs
+        .map
+        .keys()
+        .filter_map(|file| Some((file.clone(), docs.read_source(file)?)))
+        .collect::<Vec<_>>();
+
+    for (file, source) in sources {
+        let lines = source.lines().collect::<Vec<_>>();
+        let Some(list) = docs.map.get_mut(&file) else {
+            continue;
+        };
+
+        for missing in list.iter_mut().filter(|m| is_undocumented(&m.code)) {
+            if Comment::find(&lines, missing.message.row).is_none() {
+                continue;
+            }
+            // items from a macro are already labelled with it
+            missing.label = Some(match missing.label.take() {
+                Some(label) => format!("{label}; {HINT}"),
+                None => HINT.to_string(),
+            });
+        }
+    }
+}
+
+/// a comment that was, or would be, turned into a doc comment
+pub struct Fixed {
+    pub file: String,
+    pub row: usize,
+    // the lines before and after
+    pub lines: Vec<(String, String)>,
+}
+
+/// turns the regular comments above undocumented items into doc comments
+///
+/// the files are only changed with `write`, otherwise this is a dry run
+pub fn fix(docs: &MissingDocs, write: bool) -> anyhow::Result<Vec<Fixed>> {
+    let mut fixed = vec![];
+
+    for (file, list) in &docs.map {
+        let Some(path) = docs.locate(file) else {
+            continue;
+        };
+        let Ok(source) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let lines = source.lines().collect::<Vec<_>>();
+
+        let comments = list
+            .iter()
+            .filter(|m| is_undocumented(&m.code))
+            .filter_map(|m| Comment::find(&lines, m.message.row))
+            .map(|c| (c.start, c))
+            .collect::<BTreeMap<_, _>>();
+
+        if comments.is_empty() {
+            continue;
+        }
+
+        let name = file.to_string_lossy();
+        let mut changes = comments
+            .keys()
+            .map(|&start| {
+                (
+                    start,
+                    Fixed {
+                        file: name.to_string(),
+                        row: start + 1,
+                        lines: vec![],
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let mut out = String::with_capacity(source.len() + comments.len());
+        for (i, line) in source.split_inclusive('\n').enumerate() {
+            let comment = comments.values().find(|c| (c.start..=c.end).contains(&i));
+
+            let indent = line.len() - line.trim_start().len();
+            let (head, tail) = line.split_at(indent);
+            let before = out.len();
+            match comment {
+                Some(c) if c.block && i == c.start => {
+                    out.push_str(head);
+                    out.push_str("/**");
+                    out.push_str(&tail[2..]);
+                }
+                Some(c) if !c.block => {
+                    out.push_str(head);
+                    out.push('/');
+                    out.push_str(tail);
+                }
+                _ => out.push_str(line),
+            }
+
+            if let Some(c) = comment.filter(|_| out[before..] != *line) {
+                let after = out[before..].trim_end_matches(['\r', '\n']).to_string();
+                let line = line.trim_end_matches(['\r', '\n']).to_string();
+                changes.get_mut(&c.start).unwrap().lines.push((line, after));
+            }
+        }
+
+        if write {
+            std::fs::write(&path, out)
+                .with_context(|| anyhow::anyhow!("cannot write {}", path.display()))?;
+        }
+
+        fixed.extend(changes.into_values());
+    }
+
+    Ok(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the comment above the item on the last line
+    fn find(source: &str) -> Option<(usize, usize)> {
+        let lines = source.lines().collect::<Vec<_>>();
+        Comment::find(&lines, lines.len()).map(|c| (c.start, c.end))
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            find("// makes a thing\n// from parts\npub fn thing() {}"),
+            Some((0, 1))
+        );
+        assert_eq!(
+            find("/* makes a thing */\n#[inline]\npub fn thing() {}"),
+            Some((0, 0))
+        );
+        assert_eq!(find("/// makes a thing\npub fn thing() {}"), None);
+
+        for source in [
+            "// SAFETY: the pointer is valid\npub unsafe fn thing() {}",
+            "// NOTE: this is slow\npub fn thing() {}",
+            "// Copyright 2024 someone\n// SPDX-License-Identifier: MIT\npub fn thing() {}",
+            "/* Copyright (c) someone */\npub fn thing() {}",
+            "// let old = thing();\n// old.run();\npub fn thing() {}",
+            "// fn old_thing() {\n// }\npub fn thing() {}",
+            "// makes a thing\n// SAFETY: it's fine\npub fn thing() {}",
+        ] {
+            assert_eq!(find(source), None, "{source}");
+        }
+    }
+}
@@ -6,9 +6,23 @@ use std::{
 
 use anyhow::Context as _;
 
+use crate::{args::ClassifyKind, source::Item, visit::PathFilter};
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Config {
     pub theme: Theme,
+    #[serde(default)]
+    pub checks: Checks,
+    #[serde(default)]
+    pub rules: HashMap<String, Rule>,
+    #[serde(default)]
+    pub placeholders: Placeholders,
+    #[serde(default)]
+    pub filter: Vec<Filter>,
+    #[serde(default)]
+    pub features: Features,
+    #[serde(default)]
+    pub badge: Badge,
 }
 
 impl Default for Config {
@@ -71,6 +85,47 @@ impl Config {
         })
     }
 
+    pub fn rules(&self) -> anyhow::Result<Vec<(ClassifyKind, &Rule)>> {
+        self.rules
+            .iter()
+            .map(|(key, rule)| {
+                let kind = ClassifyKind::from_key(key)
+                    .with_context(|| anyhow::anyhow!("unknown kind for rules: {key}"))?;
+                Ok((kind, rule))
+            })
+            .collect()
+    }
+
+    pub fn filters(&self) -> anyhow::Result<Vec<PathFilter>> {
+        let kinds = |keys: &[String]| {
+            keys.iter()
+                .map(|key| {
+                    ClassifyKind::from_key(key)
+                        .with_context(|| anyhow::anyhow!("unknown kind for filter: {key}"))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+
+        self.filter
+            .iter()
+            .map(|filter| {
+                let paths = filter
+                    .paths
+                    .iter()
+                    .map(|path| {
+                        glob::Pattern::new(path)
+                            .with_context(|| anyhow::anyhow!("invalid glob for filter: {path}"))
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                Ok(PathFilter {
+                    paths,
+                    include: kinds(&filter.include)?,
+                    exclude: kinds(&filter.exclude)?,
+                })
+            })
+            .collect()
+    }
+
     pub fn get_config_path() -> Option<PathBuf> {
         directories::ProjectDirs::from(
             Self::QUALIFIER, //
@@ -81,6 +136,200 @@ impl Config {
     }
 }
 
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Checks {
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default)]
+    pub quality: bool,
+    #[serde(default)]
+    pub quality_lints: Vec<String>,
+    #[serde(default)]
+    pub rustdoc: bool,
+    #[serde(default)]
+    pub doctests: bool,
+    #[serde(default)]
+    pub examples: bool,
+    #[serde(default)]
+    pub placeholders: bool,
+    #[serde(default)]
+    pub audit_allows: bool,
+    #[serde(default)]
+    pub feature_docs: bool,
+    #[serde(default)]
+    pub manifest: bool,
+}
+
+impl Checks {
+    pub fn quality_lints(&self) -> anyhow::Result<Vec<String>> {
+        self.quality_lints
+            .iter()
+            .map(|lint| {
+                let lint = crate::visit::normalize_lint(lint);
+                let lint = match lint.strip_prefix("clippy::") {
+                    Some(..) => lint,
+                    None => format!("clippy::{lint}"),
+                };
+                anyhow::ensure!(
+                    lint.contains("doc"),
+                    "only documentation lints can be added to the quality checks: {lint}"
+                );
+                Ok(lint)
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Placeholders {
+    // markers that make the docs a placeholder when they lead them, or are most of the words
+    #[serde(default = "Placeholders::default_patterns")]
+    pub patterns: Vec<String>,
+    // docs with fewer words than this are placeholders
+    #[serde(default = "Placeholders::default_min_words")]
+    pub min_words: usize,
+}
+
+impl Placeholders {
+    fn default_patterns() -> Vec<String> {
+        ["TODO", "FIXME", "XXX", "lorem"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    const fn default_min_words() -> usize {
+        2
+    }
+}
+
+impl Default for Placeholders {
+    fn default() -> Self {
+        Self {
+            patterns: Self::default_patterns(),
+            min_words: Self::default_min_words(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Features {
+    // a dotted path to a table in the manifest that maps each feature to its description
+    #[serde(default = "Features::default_table")]
+    pub table: String,
+}
+
+impl Features {
+    fn default_table() -> String {
+        String::from("package.metadata.cds.features")
+    }
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            table: Self::default_table(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Badge {
+    // the text on the left side of the badge
+    #[serde(default = "Badge::default_label")]
+    pub label: String,
+    // the color of the highest threshold the coverage reaches is used
+    #[serde(default = "Badge::default_thresholds")]
+    pub thresholds: Vec<Threshold>,
+}
+
+impl Badge {
+    fn default_label() -> String {
+        String::from("docs")
+    }
+
+    fn default_thresholds() -> Vec<Threshold> {
+        [
+            (0, Color(0xe0, 0x5d, 0x44)),
+            (50, Color(0xdf, 0xb3, 0x17)),
+            (80, Color(0x97, 0xca, 0x00)),
+            (95, Color(0x44, 0xcc, 0x11)),
+        ]
+        .into_iter()
+        .map(|(coverage, color)| Threshold { coverage, color })
+        .collect()
+    }
+
+    pub fn color(&self, coverage: u32) -> Color {
+        self.thresholds
+            .iter()
+            .filter(|threshold| threshold.coverage <= coverage)
+            .max_by_key(|threshold| threshold.coverage)
+            .map_or(Color(0x9f, 0x9f, 0x9f), |threshold| threshold.color)
+    }
+}
+
+impl Default for Badge {
+    fn default() -> Self {
+        Self {
+            label: Self::default_label(),
+            thresholds: Self::default_thresholds(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Threshold {
+    // a percentage
+    pub coverage: u32,
+    pub color: Color,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Filter {
+    // globs relative to the manifest directory
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Rule {
+    // required on every documented item of this kind
+    #[serde(default)]
+    pub sections: Vec<String>,
+    // required on unsafe items of this kind
+    #[serde(default)]
+    pub unsafe_sections: Vec<String>,
+    // required on items of this kind that return a `Result`
+    #[serde(default)]
+    pub fallible_sections: Vec<String>,
+}
+
+impl Rule {
+    pub fn required<'a>(&'a self, item: &Item) -> impl Iterator<Item = &'a str> + use<'a> {
+        let fallible = item.returns().is_some_and(|ret| ret.contains("Result"));
+        let unsafe_sections = if item.is_unsafe {
+            &*self.unsafe_sections
+        } else {
+            &[]
+        };
+        let fallible_sections = if fallible {
+            &*self.fallible_sections
+        } else {
+            &[]
+        };
+
+        self.sections
+            .iter()
+            .chain(unsafe_sections)
+            .chain(fallible_sections)
+            .map(|s| s.trim_start_matches('#').trim())
+    }
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Theme {
     pub file_header: Option<Style>,
@@ -91,6 +340,17 @@ pub struct Theme {
     pub code: Option<Style>,
     #[serde(default)]
     pub kinds: HashMap<String, Style>,
+    #[serde(default)]
+    pub lints: HashMap<String, Style>,
+}
+
+impl Theme {
+    pub fn lint(&self, code: &str) -> Option<Style> {
+        self.lints
+            .iter()
+            .find(|(lint, _)| crate::visit::lint_matches(lint, code))
+            .map(|(_, style)| *style)
+    }
 }
 
 impl From<Style> for anstyle::Style {
@@ -222,3 +482,15 @@ impl FromStr for Color {
         Err(String::from("invalid color"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_placeholders() {
+        let placeholders = toml::from_str::<Placeholders>("min_words = 3").unwrap();
+        assert_eq!(placeholders.min_words, 3);
+        assert_eq!(placeholders.patterns, Placeholders::default().patterns);
+    }
+}
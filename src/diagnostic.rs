@@ -0,0 +1,139 @@
+use crate::visit::{MissingDocs, Snippet, Spanned};
+
+#[derive(serde::Deserialize)]
+struct Line {
+    reason: String,
+    message: Option<Diagnostic>,
+    target: Option<Target>,
+}
+
+// the target cargo was building, which also re-emits the diagnostics of the lib for the other targets
+#[derive(serde::Deserialize)]
+struct Target {
+    kind: Vec<String>,
+    name: String,
+}
+
+impl Target {
+    // like `lib` or `bin cds`
+    fn label(&self) -> String {
+        // `rlib`, `cdylib`, `proc-macro` and so on are all libraries
+        match self.kind.first().map(|s| &**s) {
+            Some(kind @ ("bin" | "example" | "test" | "bench")) => {
+                format!("{kind} {name}", name = self.name)
+            }
+            Some("custom-build") => String::from("build script"),
+            _ => String::from("lib"),
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Diagnostic {
+    message: String,
+    code: Option<Code>,
+    spans: Vec<Span>,
+}
+
+#[derive(serde::Deserialize)]
+struct Code {
+    code: String,
+}
+
+#[derive(serde::Deserialize)]
+struct Span {
+    file_name: String,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+    text: Vec<Text>,
+    expansion: Option<Box<Expansion>>,
+}
+
+// the macro invocation a span was expanded from
+#[derive(serde::Deserialize)]
+struct Expansion {
+    span: Span,
+    macro_decl_name: String,
+}
+
+#[derive(serde::Deserialize)]
+struct Text {
+    text: String,
+    highlight_start: usize,
+    highlight_end: usize,
+}
+
+/// adds the diagnostics from the json output of a cargo command to `docs`
+///
+/// rustdoc's diagnostics are labelled with their lint, since their messages don't say what they are
+pub fn read(stdout: &str, docs: &mut MissingDocs, label_code: bool) {
+    for line in stdout.lines() {
+        let Ok(Line {
+            reason,
+            message: Some(diagnostic),
+            target,
+        }) = serde_json::from_str(line)
+        else {
+            continue;
+        };
+
+        if reason != "compiler-message" {
+            continue;
+        }
+
+        let Some(code) = diagnostic.code else {
+            continue;
+        };
+
+        let Some(primary) = diagnostic.spans.iter().find(|s| s.is_primary) else {
+            continue;
+        };
+
+        let Some((span, expanded)) = call_site(primary, docs) else {
+            continue;
+        };
+
+        let text = span
+            .text
+            .iter()
+            .map(|text| Snippet {
+                data: text.text.clone(),
+                start: text.highlight_start,
+                end: text.highlight_end,
+            })
+            .collect();
+
+        let label = match expanded {
+            Some(name) => Some(format!("expanded from `{name}`")),
+            None if label_code => Some(code.code.clone()),
+            None => None,
+        };
+
+        docs.set_target(target.as_ref().map(Target::label));
+        docs.report(
+            &span.file_name,
+            &code.code,
+            Spanned::new(diagnostic.message, span.line_start, span.column_start),
+            text,
+            label,
+        );
+    }
+    docs.set_target(None);
+}
+
+// items made by a macro are reported where the outermost macro of this crate is invoked,
+// so each invocation is its own result
+fn call_site<'a>(primary: &'a Span, docs: &MissingDocs) -> Option<(&'a Span, Option<&'a str>)> {
+    let mut found = docs.is_local(&primary.file_name).then_some((primary, None));
+
+    let mut span = primary;
+    while let Some(expansion) = &span.expansion {
+        span = &expansion.span;
+        if docs.is_local(&span.file_name) {
+            found = Some((span, Some(&*expansion.macro_decl_name)));
+        }
+    }
+
+    found
+}
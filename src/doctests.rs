@@ -0,0 +1,148 @@
+use std::{path::Path, process::Command};
+
+use anyhow::Context as _;
+
+use crate::visit::{MissingDocs, Snippet, Spanned};
+
+enum Outcome {
+    Failed,
+    Ignored,
+}
+
+struct Doctest<'a> {
+    file: &'a str,
+    item: &'a str,
+    line: usize,
+    outcome: Outcome,
+}
+
+impl<'a> Doctest<'a> {
+    // test src/lib.rs - Quux::baz (line 18) ... FAILED
+    fn parse(line: &'a str) -> Option<Self> {
+        let (name, result) = line.strip_prefix("test ")?.split_once(" ... ")?;
+        let outcome = match result {
+            "FAILED" => Outcome::Failed,
+            s if s.starts_with("ignored") => Outcome::Ignored,
+            _ => return None,
+        };
+
+        let (file, rest) = name.split_once(" - ")?;
+        let (item, rest) = rest.rsplit_once("(line ")?;
+        let (line, _) = rest.split_once(')')?;
+
+        Some(Self {
+            file,
+            item: item.trim(),
+            line: line.parse().ok()?,
+            outcome,
+        })
+    }
+
+    fn message(&self) -> String {
+        let item = match self.item {
+            "" => "the crate",
+            item => item,
+        };
+        match self.outcome {
+            Outcome::Failed => format!("`{item}` has a failing doctest"),
+            Outcome::Ignored => format!("`{item}` has an ignored doctest"),
+        }
+    }
+}
+
+/// runs `cargo test --doc` and adds the failing and ignored doctests to `docs`
+pub fn gather(
+    manifest_path: &Path,
+    feature_args: &[String],
+    nightly: bool,
+    docs: &mut MissingDocs,
+) -> anyhow::Result<()> {
+    let mut cmd = Command::new("cargo");
+    if nightly {
+        cmd.arg("+nightly");
+    }
+
+    let output = cmd
+        .args(["test", "--doc", "--no-fail-fast"])
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .args(feature_args)
+        .args(["--", "--color", "never"])
+        .output()
+        .with_context(|| anyhow::anyhow!("cannot run cargo test"))?;
+
+    let stdout = std::str::from_utf8(&output.stdout)?;
+    let tests = stdout
+        .lines()
+        .filter_map(Doctest::parse)
+        .collect::<Vec<_>>();
+
+    // a failing test also fails the command, so only bail if nothing could be parsed
+    if tests.is_empty() && !output.status.success() {
+        anyhow::bail!(
+            "cargo test failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    for test in tests {
+        let label = match test.outcome {
+            Outcome::Failed => failure_reason(stdout, &test),
+            Outcome::Ignored => None,
+        };
+
+        // rustdoc names the files relative to the workspace root
+        let source = docs.read_source(Path::new(test.file)).unwrap_or_default();
+        let (row, col, text) = locate_item(&source, test.line);
+
+        docs.report(
+            test.file,
+            "cds::doctests",
+            Spanned::new(test.message(), row, col),
+            text,
+            label,
+        );
+    }
+
+    Ok(())
+}
+
+// the first line of the captured output is the most useful part of it
+fn failure_reason(stdout: &str, test: &Doctest) -> Option<String> {
+    let header = format!(
+        "---- {file} - {item} (line {line}) stdout ----",
+        file = test.file,
+        item = test.item,
+        line = test.line
+    );
+    let (_, tail) = stdout.split_once(&header)?;
+    tail.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(ToString::to_string)
+}
+
+// doctests report the line of their code block, so this walks down to the item the docs are attached to
+fn locate_item(source: &str, line: usize) -> (usize, usize, Vec<Snippet>) {
+    let lines = source.lines().enumerate().skip(line.saturating_sub(1));
+    for (i, data) in lines {
+        let trimmed = data.trim_start();
+        if trimmed.starts_with("//!") || trimmed.starts_with("#![") {
+            break;
+        }
+
+        if trimmed.is_empty() || trimmed.starts_with("///") || trimmed.starts_with("#[") {
+            continue;
+        }
+
+        let col = data.len() - trimmed.len() + 1;
+        let snippet = Snippet {
+            data: data.to_string(),
+            start: col,
+            end: data.len() + 1,
+        };
+        return (i + 1, col, vec![snippet]);
+    }
+
+    (line, 1, vec![])
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::BTreeMap,
+    io::Write as _,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context as _;
+
+use crate::{args::ClassifyKind, blame, visit::MissingDocs};
+
+// older snapshots are summarized in the table
+const MAX_ROWS: usize = 20;
+// and left out of the sparkline
+const MAX_SPARKLINE: usize = 60;
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// the number of results at some point in time, one line in the history file
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct Snapshot {
+    pub commit: Option<String>,
+    // seconds since the unix epoch
+    pub timestamp: u64,
+    pub total: usize,
+    #[serde(default)]
+    pub kinds: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub files: BTreeMap<String, usize>,
+}
+
+impl Snapshot {
+    fn new(root: &Path, docs: &MissingDocs) -> Self {
+        let mut this = Self {
+            commit: commit(root),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            ..Self::default()
+        };
+
+        for (file, list) in &docs.map {
+            for missing in list {
+                let (kind, _) =
+                    ClassifyKind::parse(&missing.message.item).unwrap_or((ClassifyKind::Other, 0));
+                *this.kinds.entry(kind.as_key().to_string()).or_default() += 1;
+            }
+            this.files
+                .insert(file.to_string_lossy().to_string(), list.len());
+            this.total += list.len();
+        }
+        this
+    }
+
+    fn short_commit(&self) -> &str {
+        self.commit
+            .as_deref()
+            .map_or("-", |commit| &commit[..commit.len().min(7)])
+    }
+}
+
+/// appends a snapshot of `docs` to the history file
+pub fn record(root: &Path, path: &Path, docs: &MissingDocs) -> anyhow::Result<()> {
+    let snapshot = Snapshot::new(root, docs);
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| anyhow::anyhow!("cannot open {path}", path = path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(&snapshot)?)?;
+
+    println!(
+        "recorded {total} results at {commit} in {path}",
+        total = snapshot.total,
+        commit = snapshot.short_commit(),
+        path = path.display()
+    );
+    Ok(())
+}
+
+/// shows a table and a sparkline of the totals, and what changed since the previous snapshot
+pub fn show(path: &Path) -> anyhow::Result<()> {
+    let snapshots = load(path)?;
+    if snapshots.is_empty() {
+        println!(
+            "no snapshots in {path}, add one with `cds history record`",
+            path = path.display()
+        );
+        return Ok(());
+    }
+
+    let reset = anstyle::Reset;
+    let dimmed = anstyle::Style::new().dimmed();
+
+    let skipped = snapshots.len().saturating_sub(MAX_ROWS);
+    anstream::println!("{dimmed}date        commit   total  change{reset}");
+    if skipped > 0 {
+        anstream::println!("{dimmed}... {skipped} earlier snapshots{reset}");
+    }
+    for (i, snapshot) in snapshots.iter().enumerate().skip(skipped) {
+        let change = match i.checked_sub(1).map(|prev| &snapshots[prev]) {
+            Some(prev) => delta(prev.total, snapshot.total),
+            None => String::new(),
+        };
+        anstream::println!(
+            "{date}  {commit:7}  {total:5}  {change}",
+            date = blame::date(snapshot.timestamp),
+            commit = snapshot.short_commit(),
+            total = snapshot.total,
+        );
+    }
+
+    let totals = snapshots
+        .iter()
+        .rev()
+        .take(MAX_SPARKLINE)
+        .rev()
+        .map(|snapshot| snapshot.total)
+        .collect::<Vec<_>>();
+    anstream::println!();
+    anstream::println!("trend {}", sparkline(&totals));
+
+    if let [.., prev, last] = &*snapshots {
+        anstream::println!();
+        anstream::println!("since the previous snapshot:");
+        for (name, prev, last) in [
+            ("kinds", &prev.kinds, &last.kinds),
+            ("files", &prev.files, &last.files),
+        ] {
+            let changes = changes(prev, last);
+            if changes.is_empty() {
+                continue;
+            }
+            anstream::println!("  {dimmed}{name}{reset}");
+            for (key, before, after) in changes {
+                anstream::println!("    {key}: {before} -> {after} {}", delta(before, after));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn load(path: &Path) -> anyhow::Result<Vec<Snapshot>> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| anyhow::anyhow!("cannot read {path}", path = path.display()))
+        }
+    };
+
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).with_context(|| {
+                anyhow::anyhow!(
+                    "invalid snapshot at {path}:{row}",
+                    path = path.display(),
+                    row = i + 1
+                )
+            })
+        })
+        .collect()
+}
+
+// the keys whose count changed, missing keys count as zero
+fn changes(
+    prev: &BTreeMap<String, usize>,
+    last: &BTreeMap<String, usize>,
+) -> Vec<(String, usize, usize)> {
+    let mut keys = prev.keys().chain(last.keys()).collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .map(|key| {
+            let count = |map: &BTreeMap<String, usize>| map.get(key).copied().unwrap_or(0);
+            (key.clone(), count(prev), count(last))
+        })
+        .filter(|(_, before, after)| before != after)
+        .collect()
+}
+
+// more results is more debt, so increases are red
+fn delta(before: usize, after: usize) -> String {
+    let reset = anstyle::Reset;
+    let red = anstyle::Style::new().fg_color(Some(anstyle::AnsiColor::Red.into()));
+    let green = anstyle::Style::new().fg_color(Some(anstyle::AnsiColor::Green.into()));
+    match after.cmp(&before) {
+        std::cmp::Ordering::Greater => format!("{red}+{}{reset}", after - before),
+        std::cmp::Ordering::Less => format!("{green}-{}{reset}", before - after),
+        std::cmp::Ordering::Equal => String::from("0"),
+    }
+}
+
+fn sparkline(totals: &[usize]) -> String {
+    let (min, max) = (
+        totals.iter().copied().min().unwrap_or(0),
+        totals.iter().copied().max().unwrap_or(0),
+    );
+    let range = (max - min).max(1);
+    totals
+        .iter()
+        .map(|total| BARS[(total - min) * (BARS.len() - 1) / range])
+        .collect()
+}
+
+// the commit the working tree is at, if this is a git repository
+fn commit(root: &Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,187 @@
+use std::{collections::BTreeMap, fmt::Write as _, path::Path};
+
+use anyhow::Context as _;
+
+use crate::{
+    args::ClassifyKind,
+    render::partition,
+    visit::{Missing, MissingDocs},
+};
+
+const STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; margin: 2em; color: #222; background: #fafafa; }
+h1 { font-size: 1.4em; }
+.summary { margin-bottom: 1em; }
+.filters { display: flex; flex-wrap: wrap; gap: 0.3em 1em; margin: 0.5em 0 1em; }
+.filters h2 { width: 100%; font-size: 1em; margin: 0.5em 0 0; }
+.filters label { white-space: nowrap; }
+.controls button { margin-right: 0.5em; }
+details.file { background: #fff; border: 1px solid #ddd; border-radius: 4px; margin: 0.5em 0; }
+details.file > summary { cursor: pointer; padding: 0.5em; font-family: monospace; font-weight: bold; }
+.count { color: #888; font-weight: normal; }
+.result { border-top: 1px solid #eee; padding: 0.4em 0.8em; }
+.location { font-family: monospace; color: #888; margin-right: 0.5em; }
+.tag { font-size: 0.8em; color: #555; background: #eee; border-radius: 3px; padding: 0 0.3em; margin-left: 0.3em; }
+pre { background: #f4f4f4; padding: 0.4em; margin: 0.3em 0 0; overflow-x: auto; }
+mark { background: #fde2ee; color: #a03; }
+"#;
+
+// hides the results whose kind or lint is unchecked, and the files without any results left
+const SCRIPT: &str = r#"
+const boxes = [...document.querySelectorAll('.filters input')];
+function update() {
+  const off = new Set(boxes.filter(b => !b.checked).map(b => b.dataset.filter));
+  let total = 0;
+  for (const file of document.querySelectorAll('details.file')) {
+    let shown = 0;
+    for (const row of file.querySelectorAll('.result')) {
+      row.hidden = off.has('kind:' + row.dataset.kind) || off.has('lint:' + row.dataset.lint);
+      if (!row.hidden) shown++;
+    }
+    file.hidden = shown === 0;
+    file.querySelector('.count').textContent = '(' + shown + ')';
+    total += shown;
+  }
+  document.getElementById('shown').textContent = total;
+}
+boxes.forEach(b => b.addEventListener('change', update));
+function expand(open) {
+  document.querySelectorAll('details.file').forEach(d => d.open = open);
+}
+"#;
+
+/// writes `docs` as a single html page, with everything inline so it works offline
+pub fn write(docs: &MissingDocs, root: &Path, output: &Path) -> anyhow::Result<()> {
+    let title = root.file_name().map_or_else(
+        || String::from("cds"),
+        |name| name.to_string_lossy().to_string(),
+    );
+
+    let total = docs.map.values().map(Vec::len).sum::<usize>();
+    let mut kinds = BTreeMap::<&str, usize>::new();
+    let mut lints = BTreeMap::<&str, usize>::new();
+    for missing in docs.map.values().flatten() {
+        *kinds.entry(kind(missing)).or_default() += 1;
+        *lints.entry(&missing.code).or_default() += 1;
+    }
+
+    let mut out = String::new();
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(
+        out,
+        "<title>missing documentation in {}</title>",
+        escape(&title)
+    )?;
+    writeln!(out, "<style>{STYLE}</style>\n</head>\n<body>")?;
+    writeln!(out, "<h1>missing documentation in {}</h1>", escape(&title))?;
+    writeln!(
+        out,
+        "<div class=\"summary\"><span id=\"shown\">{total}</span> of {total} results in {files} files</div>",
+        files = docs.map.len()
+    )?;
+
+    writeln!(out, "<div class=\"filters\">")?;
+    for (name, counts) in [("kind", &kinds), ("lint", &lints)] {
+        writeln!(out, "<h2>{name}s</h2>")?;
+        for (key, count) in counts {
+            writeln!(
+                out,
+                "<label><input type=\"checkbox\" checked data-filter=\"{name}:{key}\"> {key} <span class=\"count\">({count})</span></label>",
+                key = escape(key)
+            )?;
+        }
+    }
+    writeln!(out, "</div>")?;
+
+    writeln!(
+        out,
+        "<div class=\"controls\"><button onclick=\"expand(true)\">expand all</button><button onclick=\"expand(false)\">collapse all</button></div>"
+    )?;
+
+    for (file, list) in &docs.map {
+        let file = escape(&file.to_string_lossy());
+        writeln!(out, "<details class=\"file\" open>")?;
+        writeln!(
+            out,
+            "<summary>{file} <span class=\"count\">({count})</span></summary>",
+            count = list.len()
+        )?;
+        for missing in list {
+            result(&mut out, &file, missing)?;
+        }
+        writeln!(out, "</details>")?;
+    }
+
+    writeln!(out, "<script>{SCRIPT}</script>\n</body>\n</html>")?;
+
+    std::fs::write(output, out)
+        .with_context(|| anyhow::anyhow!("cannot write {path}", path = output.display()))
+}
+
+fn result(out: &mut String, file: &str, missing: &Missing) -> std::fmt::Result {
+    writeln!(
+        out,
+        "<div class=\"result\" data-kind=\"{kind}\" data-lint=\"{lint}\">",
+        kind = kind(missing),
+        lint = escape(&missing.code)
+    )?;
+    write!(
+        out,
+        "<span class=\"location\">{file}:{row}:{col}</span>{message}",
+        row = missing.message.row,
+        col = missing.message.col,
+        message = escape(&missing.message.item)
+    )?;
+
+    let mut tags = vec![missing.code.clone()];
+    tags.extend(missing.label.clone());
+    tags.extend(missing.level.map(|level| level.as_key().to_string()));
+    tags.extend(
+        missing
+            .origins
+            .iter()
+            .map(|origin| format!("from {origin}")),
+    );
+    if let Some(blame) = &missing.blame {
+        tags.push(format!("{} {}", blame.author, blame.date()));
+    }
+    for tag in tags {
+        write!(out, "<span class=\"tag\">{}</span>", escape(&tag))?;
+    }
+    writeln!(out)?;
+
+    // this string check is because they attach spans to the whole crate
+    if !missing.message.item.ends_with("the crate") {
+        let lines = partition(&missing.text)
+            .map(|(head, middle, tail)| {
+                format!(
+                    "{}<mark>{}</mark>{}",
+                    escape(head),
+                    escape(middle),
+                    escape(tail)
+                )
+            })
+            .collect::<Vec<_>>();
+        if !lines.is_empty() {
+            writeln!(out, "<pre>{}</pre>", lines.join("\n"))?;
+        }
+    }
+
+    writeln!(out, "</div>")
+}
+
+// unknown messages are the `other` kind, like in the terminal
+fn kind(missing: &Missing) -> &'static str {
+    ClassifyKind::parse(&missing.message.item)
+        .map_or(ClassifyKind::Other, |(kind, _)| kind)
+        .as_key()
+}
+
+pub fn escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
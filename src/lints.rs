@@ -0,0 +1,141 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{manifest, visit::normalize_lint};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Level {
+    Allow,
+    Expect,
+    Warn,
+    Deny,
+    Forbid,
+}
+
+impl Level {
+    pub const fn as_key(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Expect => "expect",
+            Self::Warn => "warn",
+            Self::Deny => "deny",
+            Self::Forbid => "forbid",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Some(match key {
+            "allow" => Self::Allow,
+            "expect" => Self::Expect,
+            "warn" => Self::Warn,
+            "deny" => Self::Deny,
+            "forbid" => Self::Forbid,
+            _ => return None,
+        })
+    }
+}
+
+/// the lint levels the project configured for itself
+#[derive(Clone, Debug, Default)]
+pub struct Levels {
+    map: HashMap<String, Level>,
+}
+
+impl Levels {
+    /// reads the `[lints]` table of the manifest and the lint attributes of the crate roots
+    pub fn load(manifest_path: &Path) -> anyhow::Result<Self> {
+        let mut this = Self::default();
+
+        let manifest = manifest::read(manifest_path)?;
+        let lints = match manifest.get("lints").and_then(|s| s.as_table()) {
+            // this inherits from the `[workspace.lints]` table
+            Some(lints) if lints.get("workspace").and_then(|s| s.as_bool()) == Some(true) => {
+                manifest::workspace(manifest_path)?.and_then(|(_, workspace)| {
+                    let lints = workspace.get("workspace")?.get("lints")?;
+                    lints.as_table().cloned()
+                })
+            }
+            Some(lints) => Some(lints.clone()),
+            None => None,
+        };
+        if let Some(lints) = lints {
+            this.extend_from_table(&lints);
+        }
+
+        // attributes in the source override the manifest
+        let mut root = manifest_path.to_path_buf();
+        root.pop();
+        for file in ["src/lib.rs", "src/main.rs"] {
+            let Ok(source) = std::fs::read_to_string(root.join(file)) else {
+                continue;
+            };
+            let (_, attributes, _) = crate::source::parse(&source);
+            for attr in attributes.iter().filter(|s| s.inner && s.start == 1) {
+                this.extend_from_attr(&attr.text);
+            }
+        }
+
+        Ok(this)
+    }
+
+    pub fn get(&self, lint: &str) -> Option<Level> {
+        self.map.get(&normalize_lint(lint)).copied()
+    }
+
+    pub fn is_allowed(&self, lint: &str) -> bool {
+        matches!(self.get(lint), Some(Level::Allow | Level::Expect))
+    }
+
+    // [lints.rust]
+    // missing_docs = "deny"
+    // [lints.clippy]
+    // missing_errors_doc = { level = "allow", priority = 1 }
+    fn extend_from_table(&mut self, lints: &toml::Table) {
+        for (tool, table) in lints {
+            let Some(table) = table.as_table() else {
+                continue;
+            };
+            for (name, value) in table {
+                let level = match value {
+                    toml::Value::String(level) => Some(&**level),
+                    toml::Value::Table(table) => table.get("level").and_then(|s| s.as_str()),
+                    _ => None,
+                };
+                let Some(level) = level.and_then(Level::from_key) else {
+                    continue;
+                };
+                let lint = match &**tool {
+                    "rust" => normalize_lint(name),
+                    tool => normalize_lint(&format!("{tool}::{name}")),
+                };
+                self.map.insert(lint, level);
+            }
+        }
+    }
+
+    // #![deny(missing_docs, clippy::missing_errors_doc)]
+    fn extend_from_attr(&mut self, attr: &str) {
+        let attr = attr
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>();
+
+        let Some((level, rest)) = attr
+            .strip_prefix("#![")
+            .and_then(|s| s.strip_suffix(")]"))
+            .and_then(|s| s.split_once('('))
+        else {
+            return;
+        };
+        let Some(level) = Level::from_key(level) else {
+            return;
+        };
+
+        // `reason = "..."` isn't a lint
+        for lint in rest
+            .split(',')
+            .filter(|s| !s.is_empty() && !s.contains('='))
+        {
+            self.map.insert(normalize_lint(lint), level);
+        }
+    }
+}
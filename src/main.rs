@@ -1,15 +1,26 @@
-use std::collections::HashSet;
-
-use chorts::Visit as _;
+use anyhow::Context as _;
 
 mod args;
-use args::Args;
+use args::{Args, History, Subcommand};
 
 mod config;
 use config::Config;
 use render::Options;
 
+mod badge;
+mod blame;
+mod checks;
+mod comments;
+mod diagnostic;
+mod doctests;
+mod history;
+mod html;
+mod lints;
+mod manifest;
+mod owners;
 mod render;
+mod rustdoc;
+mod source;
 mod visit;
 
 fn main() -> anyhow::Result<()> {
@@ -35,18 +46,44 @@ fn main() -> anyhow::Result<()> {
 
     let path = chorts::locate_manifest(&args.path)?;
 
-    let mut cmd = chorts::Command::default()
-        .with_tool(chorts::Tool::Clippy)
-        .with_flags([
-            chorts::Flag::new("-W", "missing_docs"),
-            chorts::Flag::new("-W", "clippy::empty_docs"),
-            chorts::Flag::new("-W", "clippy::suspicious_doc_comments"),
-        ])
-        .with_target(args.target)
-        .with_features(args.features)
-        .with_manifest_path(path)?;
-
-    for (extra, flag) in [
+    // showing the history doesn't need to check anything
+    if let Some(Subcommand::History {
+        command: History::Show,
+        file,
+    }) = &args.command
+    {
+        let dir = path.parent().unwrap_or(std::path::Path::new("."));
+        return history::show(&dir.join(file));
+    }
+
+    let levels = lints::Levels::load(&path)?;
+    // the project's own `allow`s are respected unless they are overridden,
+    // which needs `--force-warn` because attributes in the source win over `-W`
+    let enabled = |lint: &str| args.override_lints || !levels.is_allowed(lint);
+    let flag = if args.override_lints {
+        "--force-warn"
+    } else {
+        "-W"
+    };
+
+    let private =
+        args.private || config.checks.private || args.visibility == Some(args::Visibility::Private);
+
+    let mut lints = vec![];
+
+    let quality = args.quality || config.checks.quality;
+    let quality_lints = config.checks.quality_lints()?;
+    if quality {
+        // flags are only built once, so leaking the configured lints is fine
+        let extra = quality_lints.iter().map(|lint| &*lint.clone().leak());
+        lints.extend(visit::QUALITY_LINTS.iter().copied().chain(extra));
+    }
+
+    for (extra, lint) in [
+        (true, "missing_docs"),
+        (true, "clippy::empty_docs"),
+        (true, "clippy::suspicious_doc_comments"),
+        (private, "clippy::missing_docs_in_private_items"),
         (args.errors, "clippy::missing-errors-doc"),
         (args.panics, "clippy::missing-panics-doc"),
         (args.safety, "clippy::missing-safety-doc"),
@@ -54,26 +91,218 @@ fn main() -> anyhow::Result<()> {
         (args.safety, "clippy::undocumented_unsafe_blocks"),
     ] {
         if extra {
-            cmd = cmd.with_flag(chorts::Flag::new("-W", flag))
+            lints.push(lint);
         }
     }
+    lints.retain(|lint| enabled(lint));
 
-    if args.nightly {
-        cmd = cmd.with_toolchain(chorts::Toolchain::Nightly);
+    let mut root = path.clone();
+    root.pop();
+    let workspace_root = match manifest::workspace(&path)? {
+        Some((mut workspace, _)) => {
+            workspace.pop();
+            workspace
+        }
+        None => root.clone(),
+    };
+
+    let files = args.filter.with_roots(root.clone(), workspace_root);
+    let mut docs = visit::MissingDocs::new(files, args.include, args.exclude, args.visibility)
+        .with_lints(quality_lints)
+        .with_levels(levels.clone())
+        .with_path_filters(config.filters()?)
+        .with_codes(args.lints)
+        .with_targets(args.show_origin);
+
+    let clippy = Clippy {
+        path: &path,
+        flag,
+        lints: &lints,
+        nightly: args.nightly,
+    };
+
+    // each feature set is checked on its own, so the results can say where they came from
+    let features = match args.feature_matrix {
+        Some(matrix) => manifest::feature_sets(&path, matrix)?
+            .into_iter()
+            .map(|set| {
+                let label = if set.is_empty() {
+                    String::from("no features")
+                } else {
+                    format!("features {set}", set = set.join("+"))
+                };
+                let mut flags = vec![String::from("--no-default-features")];
+                if !set.is_empty() {
+                    flags.extend([String::from("--features"), set.join(",")]);
+                }
+                (Some(label), flags)
+            })
+            .collect(),
+        None => vec![(None, args.feature_args.clone())],
+    };
+
+    for (features, feature_flags) in &features {
+        docs.set_origin(features.clone());
+        clippy.gather(&[&*args.target_args, &**feature_flags].concat(), &mut docs)?;
+    }
+    docs.set_origin(None);
+
+    // the undocumented items are the ones clippy just reported
+    if let Some(Subcommand::Badge { output }) = &args.command {
+        return badge::write(&path, output, private, &docs, &config.badge);
+    }
+
+    comments::annotate(&mut docs);
+
+    if let Some(Subcommand::Fix { yes }) = args.command {
+        docs.retain_visibility();
+        docs.retain_items(&args.items);
+        let fixed = comments::fix(&docs, yes)?;
+        for comments::Fixed { file, row, lines } in &fixed {
+            if yes {
+                println!("converted the comment at {file}:{row} into a doc comment");
+                continue;
+            }
+            println!("{file}:{row}");
+            for (before, after) in lines {
+                println!("- {before}\n+ {after}");
+            }
+        }
+        if !yes && !fixed.is_empty() {
+            println!("nothing was changed, run `cds fix --yes` to write these");
+        }
+        return Ok(());
     }
 
-    let reasons = cmd.gather()?;
+    if args.rustdoc || config.checks.rustdoc {
+        let cargo_args = [&*args.target_args, &*args.feature_args].concat();
+        let lints = visit::RUSTDOC_LINTS
+            .iter()
+            .copied()
+            .filter(|lint| enabled(lint))
+            .collect::<Vec<_>>();
+        rustdoc::gather(&path, &cargo_args, &lints, flag, args.nightly, &mut docs)?;
+    }
 
-    let set = args.filter.iter().collect::<HashSet<_>>();
-    let mut docs = visit::MissingDocs::new(set, args.include, args.exclude);
-    reasons.accept(&mut docs);
+    if args.doctests || config.checks.doctests {
+        doctests::gather(&path, &args.feature_args, args.nightly, &mut docs)?;
+    }
+
+    let rules = config.rules()?;
+    let examples = args.require_examples || config.checks.examples;
+    let placeholders = args.placeholders || config.checks.placeholders;
+    let allows = args.audit_allows || config.checks.audit_allows;
+    let feature_docs = args.feature_docs || config.checks.feature_docs;
+    if feature_docs {
+        checks::features(&path, &config.features, &mut docs)?;
+    }
+    if args.manifest_check || config.checks.manifest {
+        checks::manifest(&path, &mut docs)?;
+    }
+    if examples || placeholders || allows || feature_docs || !rules.is_empty() {
+        let files = source::scan(&path)?;
+
+        if examples {
+            checks::examples(&files, &mut docs);
+        }
+        if placeholders {
+            checks::placeholders(&files, &config.placeholders, &mut docs);
+        }
+        if allows {
+            checks::allows(&files, &mut docs);
+        }
+        if feature_docs {
+            checks::doc_cfg(&files, &mut docs);
+        }
+        checks::sections(&files, &rules, &mut docs);
+    }
+
+    docs.retain_visibility();
+    docs.retain_items(&args.items);
+
+    if args.blame {
+        blame::annotate(&mut docs)?;
+    }
+    if let Some(age) = args.older_than {
+        blame::retain_older(&mut docs, age);
+    }
+
+    if !args.owners.is_empty() || args.group_by == args::GroupBy::Owner {
+        let codeowners = owners::CodeOwners::find(&root)?
+            .with_context(|| anyhow::anyhow!("cannot find a CODEOWNERS file"))?;
+        owners::annotate(&codeowners, &mut docs);
+        owners::retain(&mut docs, &args.owners);
+    }
+
+    if let Some(Subcommand::History {
+        command: History::Record,
+        file,
+    }) = &args.command
+    {
+        return history::record(&root, &root.join(file), &docs);
+    }
 
     let options = Options {
         compact: args.compact,
+        // the feature sets are the point of the feature matrix, so they are always shown
+        show_origin: args.show_origin || args.feature_matrix.is_some(),
         show_item: args.show_item,
+        blame: args.blame,
+        group_by: args.group_by,
     };
 
-    render::show(docs, options, config);
+    match args.format {
+        args::Format::Text => render::show(docs, options, config),
+        args::Format::Html => {
+            let output = args
+                .output
+                .unwrap_or_else(|| std::path::PathBuf::from("report.html"));
+            html::write(&docs, &root, &output)?;
+            println!("wrote the report to {path}", path = output.display());
+        }
+    }
 
     Ok(())
 }
+
+struct Clippy<'a> {
+    path: &'a std::path::Path,
+    flag: &'static str,
+    lints: &'a [&'static str],
+    nightly: bool,
+}
+
+impl Clippy<'_> {
+    // the output is read directly, since the spans need to say which one is primary
+    // and which macros they were expanded from
+    fn gather(&self, cargo_args: &[String], docs: &mut visit::MissingDocs) -> anyhow::Result<()> {
+        let mut cmd = std::process::Command::new("cargo");
+        if self.nightly {
+            cmd.arg("+nightly");
+        }
+
+        cmd.arg("clippy")
+            .arg("--message-format=json")
+            .arg("--manifest-path")
+            .arg(self.path)
+            .args(cargo_args)
+            .arg("--");
+        for &lint in self.lints {
+            cmd.args([self.flag, lint]);
+        }
+
+        let output = cmd
+            .output()
+            .with_context(|| anyhow::anyhow!("cannot run cargo clippy"))?;
+
+        // a crate that denies the lints fails to build, but still has its diagnostics
+        anyhow::ensure!(
+            output.status.success() || !output.stdout.is_empty(),
+            "cargo clippy failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        diagnostic::read(std::str::from_utf8(&output.stdout)?, docs, false);
+        Ok(())
+    }
+}
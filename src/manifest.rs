@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+use crate::args::FeatureMatrix;
+
+pub fn read(path: &Path) -> anyhow::Result<toml::Table> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| anyhow::anyhow!("cannot read {path}", path = path.display()))?;
+    toml::from_str(&data)
+        .with_context(|| anyhow::anyhow!("cannot parse {path}", path = path.display()))
+}
+
+/// finds the closest manifest with a `[workspace]` table, starting at `manifest_path`
+pub fn workspace(manifest_path: &Path) -> anyhow::Result<Option<(PathBuf, toml::Table)>> {
+    let Some(dir) = manifest_path.parent() else {
+        return Ok(None);
+    };
+
+    for dir in dir.ancestors() {
+        let path = dir.join("Cargo.toml");
+        if !path.is_file() {
+            continue;
+        }
+        let manifest = read(&path)?;
+        if manifest.contains_key("workspace") {
+            return Ok(Some((path, manifest)));
+        }
+    }
+
+    Ok(None)
+}
+
+// every combination of this many features is already 256 runs of clippy
+const MAX_POWERSET_FEATURES: usize = 8;
+
+/// the feature sets to check for the features in `[features]`, without `default`
+pub fn feature_sets(
+    manifest_path: &Path,
+    matrix: FeatureMatrix,
+) -> anyhow::Result<Vec<Vec<String>>> {
+    let manifest = read(manifest_path)?;
+    let features = manifest
+        .get("features")
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flat_map(|table| table.keys())
+        .filter(|name| *name != "default")
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let sets = match matrix {
+        FeatureMatrix::Each => std::iter::once(vec![])
+            .chain(features.into_iter().map(|feature| vec![feature]))
+            .collect(),
+        FeatureMatrix::Powerset => {
+            anyhow::ensure!(
+                features.len() <= MAX_POWERSET_FEATURES,
+                "{len} features is too many for --feature-powerset, use --each-feature instead",
+                len = features.len()
+            );
+            (0..1_usize << features.len())
+                .map(|mask| {
+                    features
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| mask & (1 << i) != 0)
+                        .map(|(_, feature)| feature.clone())
+                        .collect()
+                })
+                .collect()
+        }
+    };
+    Ok(sets)
+}
+
+#[derive(serde::Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+}
+
+#[derive(serde::Deserialize)]
+struct Package {
+    manifest_path: PathBuf,
+    targets: Vec<Target>,
+}
+
+#[derive(serde::Deserialize)]
+struct Target {
+    kind: Vec<String>,
+    src_path: PathBuf,
+}
+
+/// the crate roots of the package's library and binaries, relative to the package, from `cargo metadata`
+pub fn crate_roots(manifest_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let output = std::process::Command::new("cargo")
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .output()
+        .with_context(|| anyhow::anyhow!("cannot run cargo metadata"))?;
+
+    anyhow::ensure!(
+        output.status.success(),
+        "cargo metadata failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let metadata: Metadata = serde_json::from_slice(&output.stdout)?;
+    let manifest_path = std::fs::canonicalize(manifest_path)?;
+    let Some(package) = metadata
+        .packages
+        .into_iter()
+        .find(|package| package.manifest_path == manifest_path)
+    else {
+        anyhow::bail!(
+            "cannot find the package of {} in cargo metadata",
+            manifest_path.display()
+        );
+    };
+
+    let dir = manifest_path.parent().unwrap_or(Path::new(""));
+    let roots = package
+        .targets
+        .into_iter()
+        // tests, examples, benches and build scripts aren't part of the documented crate
+        .filter(|target| {
+            !target
+                .kind
+                .iter()
+                .any(|kind| matches!(&**kind, "example" | "test" | "bench" | "custom-build"))
+        })
+        .filter_map(|target| Some(target.src_path.strip_prefix(dir).ok()?.to_path_buf()))
+        .collect();
+    Ok(roots)
+}
@@ -0,0 +1,149 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+use crate::visit::MissingDocs;
+
+// where github looks for the file, in order
+const LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// the owners of the files in the repository, from a `CODEOWNERS` file
+pub struct CodeOwners {
+    root: PathBuf,
+    rules: Vec<Rule>,
+}
+
+struct Rule {
+    patterns: Vec<glob::Pattern>,
+    owners: Vec<String>,
+}
+
+impl CodeOwners {
+    /// finds the `CODEOWNERS` file of the repository that `dir` is in
+    pub fn find(dir: &Path) -> anyhow::Result<Option<Self>> {
+        for dir in dir.ancestors() {
+            for location in LOCATIONS {
+                let path = dir.join(location);
+                if path.is_file() {
+                    return Self::load(dir, &path).map(Some);
+                }
+            }
+            // the file is only looked for up to the root of the repository
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        Ok(None)
+    }
+
+    fn load(root: &Path, path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read_to_string(path)
+            .with_context(|| anyhow::anyhow!("cannot read {path}", path = path.display()))?;
+
+        let mut rules = vec![];
+        for line in data.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let owners = parts
+                .take_while(|part| !part.starts_with('#'))
+                .map(String::from)
+                .collect();
+            rules.push(Rule {
+                patterns: patterns(pattern)?,
+                owners,
+            });
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            rules,
+        })
+    }
+
+    /// the owners of `path`, the last matching rule wins
+    pub fn owners(&self, path: &Path) -> &[String] {
+        let Ok(path) = path.strip_prefix(&self.root) else {
+            return &[];
+        };
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::new()
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                rule.patterns
+                    .iter()
+                    .any(|pattern| pattern.matches_path_with(path, options))
+            })
+            .map_or(&[], |rule| &rule.owners)
+    }
+}
+
+// `CODEOWNERS` uses the `.gitignore` rules, so these are turned into globs from the root
+fn patterns(pattern: &str) -> anyhow::Result<Vec<glob::Pattern>> {
+    let body = pattern.trim_start_matches('/').trim_end_matches('/');
+    // a slash anywhere but the end anchors the pattern to the root
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    let base = if anchored {
+        body.to_string()
+    } else {
+        format!("**/{body}")
+    };
+
+    // `docs/*` only matches the files directly in `docs`
+    let mut globs = vec![format!("{base}/**")];
+    if !pattern.ends_with('/') {
+        globs.push(base.clone());
+    }
+    if body.ends_with("/*") {
+        globs.remove(0);
+    }
+
+    globs
+        .iter()
+        .map(|glob| {
+            glob::Pattern::new(glob)
+                .with_context(|| anyhow::anyhow!("invalid pattern in CODEOWNERS: {pattern}"))
+        })
+        .collect()
+}
+
+/// finds the owners of each file with results
+pub fn annotate(owners: &CodeOwners, docs: &mut MissingDocs) {
+    let files = docs
+        .map
+        .keys()
+        .filter_map(|file| {
+            let path = docs.locate(file)?;
+            Some((file.clone(), owners.owners(&path).to_vec()))
+        })
+        .collect::<Vec<_>>();
+    docs.owners.extend(files);
+}
+
+/// only keeps the results in the files owned by one of `owners`
+pub fn retain(docs: &mut MissingDocs, owners: &[String]) {
+    if owners.is_empty() {
+        return;
+    }
+
+    // `@org/team` and `org/team` are the same owner
+    let normalize = |owner: &str| owner.trim_start_matches('@').to_ascii_lowercase();
+    let wanted = owners.iter().map(|s| normalize(s)).collect::<Vec<_>>();
+
+    let files = &docs.owners;
+    docs.map.retain(|file, _| {
+        files
+            .get(file)
+            .into_iter()
+            .flatten()
+            .any(|owner| wanted.contains(&normalize(owner)))
+    });
+}
@@ -1,14 +1,17 @@
-use chorts::Highlight;
+use std::{collections::BTreeMap, path::PathBuf};
 
 use crate::{
-    args::ClassifyKind,
+    args::{ClassifyKind, GroupBy},
     config::{Config, Style, Theme},
-    visit::MissingDocs,
+    visit::{Missing, MissingDocs, Snippet},
 };
 
 pub struct Options {
     pub compact: bool,
+    pub show_origin: bool,
     pub show_item: bool,
+    pub blame: bool,
+    pub group_by: GroupBy,
 }
 
 pub fn show(docs: MissingDocs, options: Options, config: Config) {
@@ -24,16 +27,52 @@ pub fn show(docs: MissingDocs, options: Options, config: Config) {
     let reset = anstyle::Reset;
 
     let padding = pad_locations(&docs);
+    let author_padding = pad_authors(&docs);
 
-    for (i, (file, messages)) in docs.map.into_iter().enumerate() {
+    for (i, (group, messages)) in group(docs, options.group_by).into_iter().enumerate() {
         if i > 0 {
             anstream::println!("  {sp}", sp = " ".repeat(padding + 1))
         }
 
-        let file = file.to_string_lossy();
-        anstream::println!("in {file_header}{file}{reset}",);
+        match options.group_by {
+            GroupBy::File => anstream::println!("in {file_header}{group}{reset}"),
+            GroupBy::Owner => anstream::println!(
+                "{file_header}{group}{reset} {location}({count}){reset}",
+                count = messages.len()
+            ),
+            _ => anstream::println!("{file_header}{group}{reset}"),
+        }
+
+        for (file, missing) in messages {
+            let file = file.to_string_lossy();
+            let mut label = match &missing.label {
+                Some(label) => format!(" {location}[{label}]{reset}"),
+                None => String::new(),
+            };
+            if options.show_origin && !missing.origins.is_empty() {
+                label.push_str(&format!(
+                    " {location}(from {origins}){reset}",
+                    origins = missing.origins.join(", ")
+                ));
+            }
+            if let Some(level) = missing.level {
+                label.push_str(&format!(
+                    " {location}({level}){reset}",
+                    level = level.as_key()
+                ));
+            }
+
+            // the author and date columns
+            let blame = match &missing.blame {
+                Some(blame) if options.blame => format!(
+                    "{file_name}{author:author_padding$}{reset} {location}{date}{reset} ",
+                    author = blame.author,
+                    date = blame.date()
+                ),
+                None if options.blame => format!("{sp:author_padding$} {sp:10} ", sp = ""),
+                _ => String::new(),
+            };
 
-        for missing in messages {
             let location = format!(
                 "{file_name}{file}{reset}:{location}{row}:{col}{reset}",
                 row = missing.message.row,
@@ -53,15 +92,21 @@ pub fn show(docs: MissingDocs, options: Options, config: Config) {
                     + count_digits(missing.message.col),
             ));
 
+            // a style for the lint replaces the message style
+            let message = match config.theme.lint(&missing.code) {
+                Some(style) => theme_style(Some(style)),
+                None => message,
+            };
+
             match Classify::classify(msg, &config.theme) {
                 Some((head, tail, style)) => {
                     let style = theme_style(Some(style));
                     anstream::println!(
-                        "  {location} {sp} {reset}{message}{head}{reset}{style}{tail}{reset}"
+                        "  {location} {sp} {blame}{reset}{message}{head}{reset}{style}{tail}{reset}{label}"
                     )
                 }
                 None => {
-                    anstream::println!("  {location} {sp} {message}{msg}{reset}")
+                    anstream::println!("  {location} {sp} {blame}{message}{msg}{reset}{label}")
                 }
             }
 
@@ -77,6 +122,34 @@ pub fn show(docs: MissingDocs, options: Options, config: Config) {
     }
 }
 
+fn group(docs: MissingDocs, group_by: GroupBy) -> BTreeMap<String, Vec<(PathBuf, Missing)>> {
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for (file, messages) in docs.map {
+        for missing in messages {
+            let keys = match group_by {
+                GroupBy::File => vec![file.to_string_lossy().to_string()],
+                GroupBy::Visibility => vec![missing.visibility.as_key().to_string()],
+                GroupBy::Author => vec![missing
+                    .blame
+                    .as_ref()
+                    .map_or_else(|| String::from("unknown author"), |b| b.author.clone())],
+                // a file with several owners is shown for each of them
+                GroupBy::Owner => match docs.owners.get(&file) {
+                    Some(owners) if !owners.is_empty() => owners.clone(),
+                    _ => vec![String::from("no owner")],
+                },
+            };
+            for key in keys {
+                groups
+                    .entry(key)
+                    .or_default()
+                    .push((file.clone(), missing.clone()));
+            }
+        }
+    }
+    groups
+}
+
 const fn count_digits(d: usize) -> usize {
     let (mut len, mut n) = (1, 1);
     while len < 20 {
@@ -105,6 +178,16 @@ fn pad_locations(docs: &MissingDocs) -> usize {
         .unwrap_or(1)
 }
 
+fn pad_authors(docs: &MissingDocs) -> usize {
+    docs.map
+        .values()
+        .flatten()
+        .filter_map(|missing| missing.blame.as_ref())
+        .map(|blame| blame.author.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
 fn theme_style(style: Option<Style>) -> anstyle::Style {
     style
         .map(anstyle::Style::from)
@@ -120,6 +203,12 @@ fn shorten(input: &str) -> &str {
         "docs for function which may panic ",
         "safe function's docs have ",
         "unsafe function's docs are ",
+        "item in documentation is ",
+        "first doc comment ",
+        "possible intra-doc link ",
+        "needless ",
+        "using ",
+        "doc ",
     ] {
         input = input.strip_prefix(prefix).unwrap_or(input)
     }
@@ -156,7 +245,8 @@ struct Classify;
 
 impl Classify {
     fn classify<'a>(input: &'a str, theme: &Theme) -> Option<(&'a str, &'a str, Style)> {
-        let (kind, offset) = ClassifyKind::parse(input)?;
+        // unknown messages are styled as a whole
+        let (kind, offset) = ClassifyKind::parse(input).unwrap_or((ClassifyKind::Other, 0));
         let key = kind.as_key();
         let style = theme.kinds.get(key)?;
         let (head, tail) = input.split_at(offset);
@@ -164,12 +254,9 @@ impl Classify {
     }
 }
 
-fn partition<'a, 'b>(
-    spans: &'b [Highlight<'a>],
-) -> impl Iterator<Item = (&'a str, &'a str, &'a str)> + use<'a, 'b>
-where
-    'b: 'a,
-{
+pub fn partition<'a>(
+    spans: &'a [Snippet],
+) -> impl Iterator<Item = (&'a str, &'a str, &'a str)> + use<'a> {
     let mut iter = spans.iter().enumerate();
     let mut left_pad = 0;
 
@@ -0,0 +1,46 @@
+use std::{path::Path, process::Command};
+
+use anyhow::Context as _;
+
+use crate::visit::MissingDocs;
+
+/// runs `cargo rustdoc` with the documentation lints enabled and adds its diagnostics to `docs`
+pub fn gather(
+    manifest_path: &Path,
+    cargo_args: &[String],
+    lints: &[&str],
+    flag: &str,
+    nightly: bool,
+    docs: &mut MissingDocs,
+) -> anyhow::Result<()> {
+    let mut flags = std::env::var("RUSTDOCFLAGS").unwrap_or_default();
+    for lint in lints {
+        flags.push_str(&format!(" {flag} {lint}"));
+    }
+
+    let mut cmd = Command::new("cargo");
+    if nightly {
+        cmd.arg("+nightly");
+    }
+
+    // the flags are passed through the environment so this works with more than one target
+    let output = cmd
+        .arg("rustdoc")
+        .arg("--message-format=json")
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .args(cargo_args)
+        .env("RUSTDOCFLAGS", flags.trim())
+        .output()
+        .with_context(|| anyhow::anyhow!("cannot run cargo rustdoc"))?;
+
+    // a crate that denies the lints fails to document, but still has its diagnostics
+    anyhow::ensure!(
+        output.status.success() || !output.stdout.is_empty(),
+        "cargo rustdoc failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    crate::diagnostic::read(std::str::from_utf8(&output.stdout)?, docs, true);
+    Ok(())
+}
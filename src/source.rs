@@ -0,0 +1,1286 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+use crate::{
+    args::{ClassifyKind, Visibility},
+    visit::{normalize_path, Snippet},
+};
+
+/// a rust source file from the package, along with the items declared in it
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub items: Vec<Item>,
+    pub attributes: Vec<Attribute>,
+    // the inner `//!` docs of the file, which belong to the module declared elsewhere
+    pub docs: Vec<String>,
+    // whether this is the root of a crate, like `src/lib.rs`
+    pub root: bool,
+    // the `mod foo;` items, by their index in `items`, and the index of their file
+    pub modules: Vec<(usize, usize)>,
+}
+
+/// an attribute, along with the lines it applies to
+#[derive(Clone, Debug)]
+pub struct Attribute {
+    pub text: String,
+    pub line: String,
+    pub row: usize,
+    pub col: usize,
+    pub inner: bool,
+    pub start: usize,
+    pub end: usize,
+    // set when this is attached to a `mod foo;`
+    pub module: Option<String>,
+}
+
+impl Attribute {
+    pub fn covers(&self, row: usize) -> bool {
+        (self.start..=self.end).contains(&row)
+    }
+
+    pub fn snippet(&self) -> Snippet {
+        Snippet {
+            data: self.line.clone(),
+            start: self.col,
+            end: self.line.len() + 1,
+        }
+    }
+}
+
+/// an item declaration found by scanning the source
+#[derive(Clone, Debug)]
+pub struct Item {
+    pub kind: ClassifyKind,
+    pub name: String,
+    pub visibility: Visibility,
+    pub row: usize,
+    pub col: usize,
+    pub line: String,
+    pub signature: String,
+    pub is_unsafe: bool,
+    pub docs: Vec<String>,
+}
+
+impl Item {
+    pub fn is_documented(&self) -> bool {
+        self.docs.iter().any(|s| !s.trim().is_empty())
+    }
+
+    pub fn has_section(&self, section: &str) -> bool {
+        self.docs.iter().any(|line| {
+            line.trim()
+                .strip_prefix('#')
+                .map(|s| s.trim_start_matches('#').trim())
+                .is_some_and(|s| s.eq_ignore_ascii_case(section))
+        })
+    }
+
+    pub fn returns(&self) -> Option<&str> {
+        self.signature.split_once("->").map(|(_, ret)| ret.trim())
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{kind} `{name}`",
+            kind = self.kind.suffixes()[0],
+            name = self.name
+        )
+    }
+
+    pub fn snippet(&self) -> Snippet {
+        Snippet {
+            data: self.line.clone(),
+            start: self.col,
+            end: self.line.len() + 1,
+        }
+    }
+}
+
+/// reads and scans the files of the package's library and binaries,
+/// following their modules from the crate roots
+pub fn scan(manifest_path: &Path) -> anyhow::Result<Vec<SourceFile>> {
+    let root = manifest_path.parent().unwrap_or(Path::new(""));
+    let roots = crate::manifest::crate_roots(manifest_path)?;
+
+    let mut paths = roots.clone();
+    let mut files = Vec::<SourceFile>::new();
+    while let Some(path) = paths.get(files.len()).cloned() {
+        let source = std::fs::read_to_string(root.join(&path))
+            .with_context(|| anyhow::anyhow!("cannot read {}", path.display()))?;
+        let (items, attributes, docs) = parse(&source);
+
+        let mut modules = vec![];
+        for (i, item) in items.iter().enumerate() {
+            // rustc reports a module without a file on its own
+            let Some(module) = module_file(root, &path, &attributes, item) else {
+                continue;
+            };
+            let index = match paths.iter().position(|path| *path == module) {
+                Some(index) => index,
+                None => {
+                    paths.push(module);
+                    paths.len() - 1
+                }
+            };
+            modules.push((i, index));
+        }
+
+        files.push(SourceFile {
+            root: roots.contains(&path),
+            path,
+            items,
+            attributes,
+            docs,
+            modules,
+        });
+    }
+
+    // the module is documented by the inner docs of its file
+    for i in 0..files.len() {
+        for (item, index) in files[i].modules.clone() {
+            let docs = files[index].docs.clone();
+            files[i].items[item].docs.extend(docs);
+        }
+    }
+
+    // a private `mod foo;` makes everything in its file private, and the files it declares
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..files.len() {
+            let hidden = files[i]
+                .modules
+                .iter()
+                .filter(|&&(item, _)| files[i].items[item].visibility == Visibility::Private)
+                .map(|&(_, index)| index)
+                .collect::<Vec<_>>();
+
+            // `#[macro_export]` macros are public from anywhere
+            for index in hidden {
+                for item in &mut files[index].items {
+                    if item.kind != ClassifyKind::Macro && item.visibility == Visibility::Public {
+                        item.visibility = Visibility::Private;
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+// the file of a `mod foo;` item in `parent`, which `#[path]` can point anywhere
+fn module_file(
+    root: &Path,
+    parent: &Path,
+    attributes: &[Attribute],
+    item: &Item,
+) -> Option<PathBuf> {
+    if item.kind != ClassifyKind::Module || !item.signature.ends_with(';') {
+        return None;
+    }
+
+    let path = attributes
+        .iter()
+        .filter(|attr| attr.start == item.row && attr.module.as_deref() == Some(&*item.name))
+        .find_map(|attr| path_attr(&attr.text));
+
+    let candidates = match path {
+        Some(path) => vec![normalize_path(&parent.parent()?.join(path))],
+        None => module_paths(parent, &item.name)?.to_vec(),
+    };
+    candidates
+        .into_iter()
+        .find(|candidate| root.join(candidate).is_file())
+}
+
+// the file in `#[path = "file.rs"]`
+fn path_attr(attr: &str) -> Option<&str> {
+    let rest = attr.strip_prefix("#[")?.trim_start().strip_prefix("path")?;
+    let rest = rest.trim_start().strip_prefix('=')?;
+    rest.split('"').nth(1)
+}
+
+// the files that `mod module;` in `parent` can be
+fn module_paths(parent: &Path, module: &str) -> Option<[PathBuf; 2]> {
+    let dir = match parent.file_stem().and_then(|s| s.to_str()) {
+        Some("lib" | "main" | "mod") | None => parent.parent()?.to_path_buf(),
+        Some(stem) => parent.parent()?.join(stem),
+    };
+
+    Some([
+        dir.join(format!("{module}.rs")),
+        dir.join(module).join("mod.rs"),
+    ])
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Scope {
+    // with its visibility from outside the crate
+    Module(Visibility),
+    Impl,
+    TraitImpl,
+    Trait(Visibility),
+    // an `extern` block
+    Foreign,
+    // the fields of a struct or union, and the variants of an enum, with its visibility
+    Fields(Visibility),
+    Variants(Visibility),
+    // the fields of a variant, which are as visible as the enum
+    Variant(Visibility),
+    Body,
+}
+
+struct Open {
+    depth: usize,
+    scope: Scope,
+    start: usize,
+    // the attributes that cover this scope
+    attrs: Vec<usize>,
+    // the module item this is the body of, for its inner docs
+    item: Option<usize>,
+}
+
+/// finds the item declarations, attributes and inner docs of the file in `source`
+///
+/// this is line based, so it only understands reasonably formatted code.
+/// the visibility of items is from outside the crate, so a `pub fn` in a private module is private
+pub fn parse(source: &str) -> (Vec<Item>, Vec<Attribute>, Vec<String>) {
+    let lines = source.lines().collect::<Vec<_>>();
+
+    let mut out = Vec::<Item>::new();
+    let mut attributes = Vec::<Attribute>::new();
+    let mut inner_docs = vec![];
+
+    let mut docs = vec![];
+    let mut attrs = vec![];
+    let mut attr = None::<Attribute>;
+
+    let mut depth = 0_usize;
+    let mut scopes = vec![Open {
+        depth: 0,
+        scope: Scope::Module(Visibility::Public),
+        start: 1,
+        attrs: vec![],
+        item: None,
+    }];
+    let mut pending = None;
+    let mut pending_item = None;
+    let mut pending_attrs = vec![];
+    let mut in_comment = false;
+    let mut literal = Literal::None;
+    // the open parentheses of a tuple variant, which don't declare members
+    let mut parens = 0_usize;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+
+        // the rest of a string from an earlier line, which can't declare anything
+        let continued = literal.is_open();
+
+        if !continued {
+            if in_comment {
+                in_comment = !trimmed.contains("*/");
+                continue;
+            }
+
+            if let Some(mut current) = attr.take() {
+                current.text.push_str(trimmed);
+                if !is_balanced(&current.text) {
+                    attr = Some(current);
+                    continue;
+                }
+                let inner = current.inner;
+                attributes.push(current);
+                if inner {
+                    scopes.last_mut().unwrap().attrs.push(attributes.len() - 1);
+                } else {
+                    attrs.push(attributes.len() - 1);
+                }
+                continue;
+            }
+
+            if let Some(doc) = trimmed.strip_prefix("///") {
+                if !doc.starts_with('/') {
+                    docs.push(doc.strip_prefix(' ').unwrap_or(doc).to_string());
+                }
+                continue;
+            }
+
+            if let Some(doc) = trimmed.strip_prefix("//!") {
+                let doc = doc.strip_prefix(' ').unwrap_or(doc).to_string();
+                match scopes.last().unwrap().item {
+                    Some(index) => out[index].docs.push(doc),
+                    None if scopes.len() == 1 => inner_docs.push(doc),
+                    None => {}
+                }
+                continue;
+            }
+
+            if trimmed.is_empty() || trimmed.starts_with("//") {
+                continue;
+            }
+
+            if trimmed.starts_with("/*") {
+                in_comment = !trimmed.contains("*/");
+                continue;
+            }
+
+            if trimmed.starts_with("#[") || trimmed.starts_with("#![") {
+                let inner = trimmed.starts_with("#!");
+                match (doc_attr(trimmed), scopes.last().unwrap().item) {
+                    (Some(doc), _) if !inner => docs.push(doc),
+                    (Some(doc), Some(index)) => out[index].docs.push(doc),
+                    (Some(doc), None) if scopes.len() == 1 => inner_docs.push(doc),
+                    _ => {}
+                }
+
+                let scope = scopes.last().unwrap();
+                let current = Attribute {
+                    text: trimmed.to_string(),
+                    line: line.to_string(),
+                    row: i + 1,
+                    col: line.len() - trimmed.len() + 1,
+                    inner,
+                    start: if inner { scope.start } else { i + 1 },
+                    end: i + 1,
+                    module: None,
+                };
+
+                if !is_balanced(trimmed) {
+                    attr = Some(current);
+                    continue;
+                }
+
+                attributes.push(current);
+                if inner {
+                    scopes.last_mut().unwrap().attrs.push(attributes.len() - 1);
+                } else {
+                    attrs.push(attributes.len() - 1);
+                }
+                continue;
+            }
+        }
+
+        let scope = scopes
+            .last()
+            .map_or(Scope::Module(Visibility::Public), |open| open.scope);
+        // whether this is reachable from outside the crate
+        let exposed = !scopes
+            .iter()
+            .any(|open| open.scope == Scope::Module(Visibility::Private));
+        let decl = Decl::parse(trimmed).filter(|_| !continued);
+        let member = member(scope, trimmed).filter(|_| !continued && parens == 0);
+
+        // the attributes are attached to this line
+        for &index in &attrs {
+            let attribute = &mut attributes[index];
+            attribute.start = i + 1;
+            attribute.end = i + 1;
+            if let Some(decl) = decl.as_ref().filter(|d| d.keyword == Keyword::Mod) {
+                if trimmed.ends_with(';') {
+                    attribute.module = Some(decl.name.to_string());
+                }
+            }
+        }
+
+        if let Some(decl) = &decl {
+            let kind = decl.kind(scope, || takes_self(&lines[i..]));
+            let exported = attrs
+                .iter()
+                .any(|&index| attributes[index].text.contains("macro_export"));
+            let visibility = match scope {
+                Scope::Trait(visibility) => visibility,
+                _ if decl.keyword == Keyword::Macro && exported => Visibility::Public,
+                _ if !exposed => Visibility::Private,
+                _ => decl.visibility,
+            };
+
+            if let Some(kind) = kind {
+                out.push(Item {
+                    kind,
+                    name: decl.name.to_string(),
+                    visibility,
+                    row: i + 1,
+                    col: line.len() - trimmed.len() + 1,
+                    line: line.to_string(),
+                    signature: signature(&lines[i..]),
+                    is_unsafe: decl.is_unsafe,
+                    docs: std::mem::take(&mut docs),
+                });
+            }
+
+            if matches!(kind, Some(ClassifyKind::Struct | ClassifyKind::Union)) {
+                out.extend(inline_fields(line, i + 1, Scope::Fields(visibility)));
+            }
+
+            pending_item = (decl.keyword == Keyword::Mod && kind.is_some()).then(|| out.len() - 1);
+            pending = Some(match decl.keyword {
+                _ if kind.is_none() => Scope::Body,
+                Keyword::Trait => Scope::Trait(visibility),
+                Keyword::Mod => Scope::Module(visibility),
+                Keyword::Struct | Keyword::Union => Scope::Fields(visibility),
+                Keyword::Enum => Scope::Variants(visibility),
+                _ => Scope::Body,
+            });
+            pending_attrs = std::mem::take(&mut attrs);
+        } else if let Some((kind, name, visibility)) = member {
+            let visibility = if exposed {
+                visibility
+            } else {
+                Visibility::Private
+            };
+            out.push(Item {
+                kind,
+                name: name.to_string(),
+                visibility,
+                row: i + 1,
+                col: line.len() - trimmed.len() + 1,
+                line: line.to_string(),
+                signature: trimmed.trim_end().trim_end_matches(',').to_string(),
+                is_unsafe: false,
+                docs: std::mem::take(&mut docs),
+            });
+
+            if let Scope::Variants(visibility) = scope {
+                out.extend(inline_fields(line, i + 1, Scope::Variant(visibility)));
+                pending = Some(Scope::Variant(visibility));
+            }
+        } else if let Some(header) = impl_header(trimmed).filter(|_| !continued) {
+            pending = Some(if header {
+                Scope::TraitImpl
+            } else {
+                Scope::Impl
+            });
+            pending_attrs = std::mem::take(&mut attrs);
+        } else if !continued && is_extern_block(trimmed) {
+            pending = Some(Scope::Foreign);
+            pending_attrs = std::mem::take(&mut attrs);
+        }
+
+        docs.clear();
+        attrs.clear();
+
+        if matches!(scope, Scope::Variants(..)) && !continued {
+            let code = trimmed.split("//").next().unwrap_or(trimmed);
+            parens += code.matches('(').count();
+            parens = parens.saturating_sub(code.matches(')').count());
+        }
+
+        for brace in literal.braces(trimmed) {
+            if brace {
+                depth += 1;
+                parens = 0;
+                scopes.push(Open {
+                    depth,
+                    scope: pending.take().unwrap_or(Scope::Body),
+                    start: i + 1,
+                    attrs: std::mem::take(&mut pending_attrs),
+                    item: pending_item.take(),
+                });
+            } else {
+                depth = depth.saturating_sub(1);
+                while scopes.len() > 1 && scopes.last().is_some_and(|open| open.depth > depth) {
+                    let open = scopes.pop().unwrap();
+                    for index in open.attrs {
+                        attributes[index].end = i + 1;
+                    }
+                }
+            }
+        }
+
+        // `struct Foo;` and `fn foo();` don't open a scope
+        if !literal.is_open() && trimmed.ends_with(';') {
+            pending = None;
+            pending_attrs.clear();
+            pending_item = None;
+        }
+    }
+
+    // anything left open goes until the end of the file
+    for open in scopes {
+        for index in open.attrs {
+            attributes[index].end = lines.len();
+        }
+    }
+
+    (out, attributes, inner_docs)
+}
+
+/// the path of the item declared at `row`, like `Mapping::dispatch`
+///
+/// doc comments and attributes are skipped, so `row` can also point at the docs of the item
+pub fn item_path(source: &str, row: usize) -> Option<String> {
+    let mut scopes = Vec::<(usize, Option<String>)>::new();
+    let mut depth = 0_usize;
+    let mut pending = None;
+    let mut in_comment = false;
+    let mut literal = Literal::None;
+
+    for (i, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+
+        // the rest of a string from an earlier line only matters for where it ends
+        if !literal.is_open() {
+            if in_comment {
+                in_comment = !trimmed.contains("*/");
+                continue;
+            }
+
+            if i + 1 >= row {
+                if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with("#[") {
+                    continue;
+                }
+                let name = declared_name(trimmed)?;
+                let mut path = scopes
+                    .iter()
+                    .filter_map(|(_, name)| name.as_deref())
+                    .collect::<Vec<_>>();
+                path.push(name);
+                return Some(path.join("::"));
+            }
+
+            if trimmed.starts_with("/*") {
+                in_comment = !trimmed.contains("*/");
+                continue;
+            }
+
+            if let Some(decl) = Decl::parse(trimmed) {
+                pending = Some(decl.name.to_string());
+            } else if impl_header(trimmed).is_some() {
+                pending = impl_type(trimmed).map(ToString::to_string);
+            }
+        }
+
+        for brace in literal.braces(trimmed) {
+            if brace {
+                depth += 1;
+                scopes.push((depth, pending.take()));
+            } else {
+                depth = depth.saturating_sub(1);
+                while scopes.last().is_some_and(|(d, _)| *d > depth) {
+                    scopes.pop();
+                }
+            }
+        }
+
+        if !literal.is_open() && trimmed.ends_with(';') {
+            pending = None;
+        }
+    }
+
+    None
+}
+
+// a struct field or variant, with its visibility if the container is public
+fn member(scope: Scope, line: &str) -> Option<(ClassifyKind, &str, Visibility)> {
+    let (kind, visibility) = match scope {
+        Scope::Fields(visibility) | Scope::Variant(visibility) => {
+            (ClassifyKind::StructField, visibility)
+        }
+        Scope::Variants(visibility) => (ClassifyKind::Variant, visibility),
+        _ => return None,
+    };
+
+    // the fields of variants are always public
+    let (public, rest) = match line.strip_prefix("pub") {
+        Some(rest) if rest.starts_with('(') => (false, rest.split_once(')')?.1.trim_start()),
+        Some(rest) if rest.starts_with(' ') => (true, rest.trim_start()),
+        _ => (!matches!(scope, Scope::Fields(..)), line),
+    };
+    let rest = rest.strip_prefix("r#").unwrap_or(rest);
+    let name = ident(rest)?;
+    let tail = rest[name.len()..].trim_start();
+
+    let found = match kind {
+        ClassifyKind::StructField => tail.starts_with(':') && !tail.starts_with("::"),
+        _ => {
+            name.starts_with(char::is_uppercase)
+                && (tail.is_empty() || tail.starts_with([',', '(', '{', '=']))
+        }
+    };
+    let visibility = if public {
+        visibility
+    } else {
+        Visibility::Private
+    };
+    found.then_some((kind, name, visibility))
+}
+
+// the fields of `Variant { a: u8, b: u8 }` and `struct Foo { a: u8 }` on a single line
+fn inline_fields(line: &str, row: usize, scope: Scope) -> Vec<Item> {
+    let Some(open) = line.find('{') else {
+        return vec![];
+    };
+    let Some(close) = line.rfind('}').filter(|&close| close > open) else {
+        return vec![];
+    };
+
+    let mut fields = vec![];
+    let mut depth = 0_usize;
+    let mut start = open + 1;
+    for (i, c) in line[..=close]
+        .char_indices()
+        .skip_while(|&(i, _)| i <= open)
+    {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' | '}' if depth == 0 => {
+                let part = &line[start..i];
+                let trimmed = part.trim_start();
+                if let Some((kind, name, visibility)) = member(scope, trimmed) {
+                    fields.push(Item {
+                        kind,
+                        name: name.to_string(),
+                        visibility,
+                        row,
+                        col: start + part.len() - trimmed.len() + 1,
+                        line: line.to_string(),
+                        signature: trimmed.trim_end().to_string(),
+                        is_unsafe: false,
+                        docs: vec![],
+                    });
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    fields
+}
+
+// items, and the struct fields and variants which aren't items
+fn declared_name(line: &str) -> Option<&str> {
+    if let Some(decl) = Decl::parse(line) {
+        return Some(decl.name);
+    }
+
+    let rest = match line.strip_prefix("pub") {
+        Some(rest) if rest.starts_with('(') => rest.split_once(')')?.1.trim_start(),
+        Some(rest) if rest.starts_with(' ') => rest.trim_start(),
+        _ => line,
+    };
+    let name = ident(rest)?;
+    let tail = rest[name.len()..].trim_start();
+    let is_field = tail.starts_with(':') && !tail.starts_with("::");
+    let is_variant = name.starts_with(char::is_uppercase)
+        && (tail.is_empty() || tail.starts_with([',', '(', '{', '=']));
+    (is_field || is_variant).then_some(name)
+}
+
+// `impl<T> Foo<T> {` and `impl Trait for Foo {` are both `Foo`
+fn impl_type(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("unsafe ").unwrap_or(line);
+    let mut rest = rest.strip_prefix("impl")?;
+    if rest.starts_with('<') {
+        let mut depth = 0;
+        let end = rest.find(|c| {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        })?;
+        rest = &rest[end + 1..];
+    }
+    let head = rest.split('{').next().unwrap_or(rest);
+    let head = head.split(" where").next().unwrap_or(head);
+    let ty = head.rsplit_once(" for ").map_or(head, |(_, ty)| ty).trim();
+    let ty = ty.trim_start_matches(['&', '*']).trim_start_matches("mut ");
+    let ty = ty.split('<').next().unwrap_or(ty);
+    ident(ty.rsplit("::").next().unwrap_or(ty))
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Keyword {
+    Fn,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    TraitAlias,
+    Type,
+    Const,
+    Static,
+    Mod,
+    Macro,
+}
+
+struct Decl<'a> {
+    visibility: Visibility,
+    keyword: Keyword,
+    name: &'a str,
+    is_unsafe: bool,
+}
+
+impl<'a> Decl<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let (visibility, mut rest) = match line.strip_prefix("pub") {
+            Some(rest) if rest.starts_with(' ') => (Visibility::Public, rest.trim_start()),
+            Some(rest) if rest.starts_with('(') => {
+                let (_, rest) = rest.split_once(')')?;
+                (Visibility::Private, rest.trim_start())
+            }
+            _ => (Visibility::Private, line),
+        };
+
+        if let Some(tail) = rest.strip_prefix("macro_rules!") {
+            return Some(Self {
+                visibility,
+                keyword: Keyword::Macro,
+                name: ident(tail.trim_start())?,
+                is_unsafe: false,
+            });
+        }
+
+        let mut is_unsafe = false;
+        loop {
+            is_unsafe |= rest.starts_with("unsafe ");
+            let next = ["default ", "async ", "unsafe ", "safe ", "auto "]
+                .iter()
+                .find_map(|q| rest.strip_prefix(q))
+                .or_else(|| {
+                    // `const fn` and `const unsafe fn`, but not `const FOO`
+                    rest.strip_prefix("const ").filter(|s| {
+                        ["fn ", "unsafe ", "async ", "extern "]
+                            .iter()
+                            .any(|q| s.starts_with(q))
+                    })
+                })
+                .or_else(|| {
+                    let tail = rest.strip_prefix("extern ")?;
+                    let (_, tail) = tail.strip_prefix('"')?.split_once('"')?;
+                    Some(tail)
+                });
+            match next {
+                Some(next) => rest = next.trim_start(),
+                None => break,
+            }
+        }
+
+        let (keyword, tail) = [
+            ("fn ", Keyword::Fn),
+            ("struct ", Keyword::Struct),
+            ("enum ", Keyword::Enum),
+            ("union ", Keyword::Union),
+            ("trait ", Keyword::Trait),
+            ("type ", Keyword::Type),
+            ("const ", Keyword::Const),
+            ("static ", Keyword::Static),
+            ("mod ", Keyword::Mod),
+        ]
+        .iter()
+        .find_map(|&(k, v)| rest.strip_prefix(k).map(|tail| (v, tail)))?;
+
+        // `trait Foo = Bar;` can't have a body
+        let keyword = match keyword {
+            Keyword::Trait if line.ends_with(';') => Keyword::TraitAlias,
+            keyword => keyword,
+        };
+
+        let tail = tail.trim_start();
+        let tail = tail.strip_prefix("mut ").unwrap_or(tail);
+        Some(Self {
+            visibility,
+            keyword,
+            name: ident(tail.trim_start())?,
+            is_unsafe,
+        })
+    }
+
+    fn kind(&self, scope: Scope, takes_self: impl Fn() -> bool) -> Option<ClassifyKind> {
+        let associated = matches!(scope, Scope::Impl | Scope::Trait(..));
+        let kind = match self.keyword {
+            _ if !matches!(
+                scope,
+                Scope::Module(..) | Scope::Impl | Scope::Trait(..) | Scope::Foreign
+            ) =>
+            {
+                return None
+            }
+            Keyword::Fn if scope == Scope::Foreign => ClassifyKind::ForeignFunction,
+            Keyword::Static if scope == Scope::Foreign => ClassifyKind::ForeignStatic,
+            Keyword::Type if scope == Scope::Foreign => ClassifyKind::ForeignType,
+            Keyword::Fn if associated && takes_self() => ClassifyKind::Method,
+            Keyword::Fn if associated => ClassifyKind::AssociatedFunction,
+            Keyword::Fn => ClassifyKind::Function,
+            Keyword::Const if associated => ClassifyKind::AssociatedConstant,
+            Keyword::Const => ClassifyKind::Constant,
+            Keyword::Type if associated => ClassifyKind::AssociatedType,
+            Keyword::Type => ClassifyKind::TypeAlias,
+            Keyword::Struct => ClassifyKind::Struct,
+            Keyword::Union => ClassifyKind::Union,
+            Keyword::Enum => ClassifyKind::Enum,
+            Keyword::Trait => ClassifyKind::Trait,
+            Keyword::TraitAlias => ClassifyKind::TraitAlias,
+            Keyword::Static => ClassifyKind::Static,
+            Keyword::Macro => ClassifyKind::Macro,
+            Keyword::Mod => ClassifyKind::Module,
+        };
+        Some(kind)
+    }
+}
+
+fn ident(input: &str) -> Option<&str> {
+    let input = input.strip_prefix("r#").unwrap_or(input);
+    let end = input
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(input.len());
+    (end > 0).then(|| &input[..end])
+}
+
+// whether `impl .. for ..` or just `impl ..`
+fn impl_header(line: &str) -> Option<bool> {
+    let rest = line.strip_prefix("unsafe ").unwrap_or(line);
+    let rest = rest.strip_prefix("impl")?;
+    if !(rest.starts_with('<') || rest.starts_with(' ')) {
+        return None;
+    }
+    let head = rest.split('{').next().unwrap_or(rest);
+    Some(head.contains(" for "))
+}
+
+// `extern "C" {`, but not `extern crate foo;`
+fn is_extern_block(line: &str) -> bool {
+    let rest = line.strip_prefix("unsafe ").unwrap_or(line);
+    rest.starts_with("extern") && !rest.ends_with(';')
+}
+
+// the declaration up to its body, joined onto a single line
+fn signature(lines: &[&str]) -> String {
+    let mut out = String::new();
+    for line in lines.iter().take(16) {
+        let line = line.trim();
+        if let Some((head, _)) = line.split_once('{') {
+            out.push_str(head.trim_end());
+            break;
+        }
+        out.push_str(line);
+        if line.ends_with(';') {
+            break;
+        }
+        out.push(' ');
+    }
+    out.trim_end().to_string()
+}
+
+fn takes_self(lines: &[&str]) -> bool {
+    let mut params = String::new();
+    for line in lines.iter().take(8) {
+        params.push_str(line);
+        if line.contains(')') {
+            break;
+        }
+    }
+
+    let Some((_, params)) = params.split_once('(') else {
+        return false;
+    };
+    let first = params.split([',', ')']).next().unwrap_or_default();
+    first
+        .split_whitespace()
+        .last()
+        .is_some_and(|s| s.ends_with("self"))
+        || first.trim_start().starts_with("self")
+        || first.contains("self:")
+}
+
+// `#[doc = ".."]` and `#![doc = ".."]`
+fn doc_attr(attr: &str) -> Option<String> {
+    let rest = attr
+        .strip_prefix("#[doc")
+        .or_else(|| attr.strip_prefix("#![doc"))?
+        .trim_start()
+        .strip_prefix('=')?;
+    let (_, rest) = rest.split_once('"')?;
+    let (doc, _) = rest.rsplit_once('"')?;
+    Some(doc.trim().to_string())
+}
+
+fn is_balanced(attr: &str) -> bool {
+    attr.matches('[').count() <= attr.matches(']').count()
+}
+
+// the string literal a line ends inside of, so the braces in multi-line strings aren't counted
+#[derive(Copy, Clone, Default, PartialEq)]
+enum Literal {
+    #[default]
+    None,
+    Str,
+    // a raw string, closed by a quote and this many `#`s
+    Raw(usize),
+}
+
+impl Literal {
+    fn is_open(self) -> bool {
+        self != Self::None
+    }
+
+    // the braces on a line, ignoring ones in strings, characters and comments. `true` is an opening brace
+    fn braces(&mut self, line: &str) -> Vec<bool> {
+        let mut out = vec![];
+        let mut iter = line.char_indices().peekable();
+        while let Some((i, c)) = iter.next() {
+            match *self {
+                Self::Str => match c {
+                    '\\' => {
+                        iter.next();
+                    }
+                    '"' => *self = Self::None,
+                    _ => {}
+                },
+                Self::Raw(hashes) => {
+                    let tail = &line[i + c.len_utf8()..];
+                    if c == '"' && tail.bytes().take_while(|&b| b == b'#').count() >= hashes {
+                        if hashes > 0 {
+                            iter.nth(hashes - 1);
+                        }
+                        *self = Self::None;
+                    }
+                }
+                Self::None => match c {
+                    '"' => *self = Self::Str,
+                    '/' if line[i..].starts_with("//") => break,
+                    'r' if raw_string(line, i).is_some() => {
+                        let hashes = raw_string(line, i).unwrap_or_default();
+                        iter.nth(hashes);
+                        *self = Self::Raw(hashes);
+                    }
+                    // `'\\u{7b}'` and `'\\''`
+                    '\'' if line[i + 1..].starts_with('\\') => {
+                        let end = line
+                            .get(i + 3..)
+                            .and_then(|tail| tail.find('\''))
+                            .map_or(line.len(), |end| i + 3 + end);
+                        while iter.next_if(|&(j, _)| j <= end).is_some() {}
+                    }
+                    '\'' if line[i..].chars().nth(2) == Some('\'') => {
+                        iter.next();
+                        iter.next();
+                    }
+                    '{' => out.push(true),
+                    '}' => out.push(false),
+                    _ => {}
+                },
+            }
+        }
+        out
+    }
+}
+
+// the number of `#`s when a raw string like `r#"` or `br"` starts at `i`
+fn raw_string(line: &str, i: usize) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let head = &line[..i];
+    let head = match head.strip_suffix(['b', 'c']) {
+        Some(rest) if !rest.ends_with(is_ident) => rest,
+        _ => head,
+    };
+    if head.ends_with(is_ident) {
+        return None;
+    }
+
+    let tail = &line[i + 1..];
+    let hashes = tail.bytes().take_while(|&b| b == b'#').count();
+    tail[hashes..].starts_with('"').then_some(hashes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(items: &[Item]) -> Vec<(&str, Visibility)> {
+        items
+            .iter()
+            .map(|item| (item.name.as_str(), item.visibility))
+            .collect()
+    }
+
+    #[test]
+    fn parse_items() {
+        let source = r#"
+/// a thing
+pub struct Thing {
+    pub field: u8,
+}
+
+impl Thing {
+    /// makes one
+    pub fn new() -> Self {
+        Self { field: 0 }
+    }
+
+    pub fn get(&self) -> u8 {
+        self.field
+    }
+}
+
+impl Default for Thing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+"#;
+        let (items, _, _) = parse(source);
+        let kinds = items.iter().map(|item| item.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ClassifyKind::Struct,
+                ClassifyKind::StructField,
+                ClassifyKind::AssociatedFunction,
+                ClassifyKind::Method,
+                ClassifyKind::Trait,
+                ClassifyKind::Method,
+            ]
+        );
+        assert_eq!(items[0].docs, ["a thing"]);
+        assert_eq!(items[2].signature, "pub fn new() -> Self");
+        assert_eq!(items[5].visibility, Visibility::Public);
+    }
+
+    #[test]
+    fn parse_members() {
+        let source = r#"
+pub struct Point {
+    /// across
+    pub x: i32,
+    pub(crate) y: i32,
+    z: i32,
+}
+
+pub struct Pair { pub a: Vec<(u8, u8)>, b: u8 }
+
+pub struct Tuple(pub u8);
+
+pub enum Shape {
+    /// round
+    Circle,
+    Square(
+        u8,
+        u8,
+    ),
+    Rect { width: u8, height: u8 },
+    Line {
+        length: u8,
+    },
+    Empty = 4,
+}
+
+enum Hidden {
+    A,
+}
+"#;
+        let (items, _, _) = parse(source);
+        let members = items
+            .iter()
+            .filter(|item| matches!(item.kind, ClassifyKind::StructField | ClassifyKind::Variant))
+            .map(|item| (&*item.name, item.visibility, item.col))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            members,
+            [
+                ("x", Visibility::Public, 5),
+                ("y", Visibility::Private, 5),
+                ("z", Visibility::Private, 5),
+                ("a", Visibility::Public, 19),
+                ("b", Visibility::Private, 41),
+                ("Circle", Visibility::Public, 5),
+                ("Square", Visibility::Public, 5),
+                ("Rect", Visibility::Public, 5),
+                ("width", Visibility::Public, 12),
+                ("height", Visibility::Public, 23),
+                ("Line", Visibility::Public, 5),
+                ("length", Visibility::Public, 9),
+                ("Empty", Visibility::Public, 5),
+                ("A", Visibility::Private, 5),
+            ]
+        );
+
+        let x = items.iter().find(|item| item.name == "x").unwrap();
+        assert_eq!(x.docs, ["across"]);
+        assert_eq!(x.signature, "pub x: i32");
+    }
+
+    #[test]
+    fn parse_multiline_strings() {
+        let source = r##"
+pub const TEMPLATE: &str = "fn main() {
+    println!(\"{}\", 1);
+";
+
+pub const RAW: &str = r#"
+    { "a": "b" {
+"#;
+
+pub const CHAR: char = '{';
+pub const ESCAPED: char = '\u{7b}';
+
+pub fn after() {}
+"##;
+        let (items, _, _) = parse(source);
+        assert_eq!(
+            names(&items),
+            [
+                ("TEMPLATE", Visibility::Public),
+                ("RAW", Visibility::Public),
+                ("CHAR", Visibility::Public),
+                ("ESCAPED", Visibility::Public),
+                ("after", Visibility::Public),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_private_modules() {
+        let source = "
+mod inner {
+    pub fn hidden() {}
+
+    pub mod deeper {
+        pub fn also_hidden() {}
+    }
+}
+
+pub mod outer {
+    pub fn shown() {}
+    pub(crate) fn scoped() {}
+}
+
+mod macros {
+    #[macro_export]
+    macro_rules! exported {
+        () => {};
+    }
+}
+
+mod file;
+pub mod other;
+";
+        let (items, _, _) = parse(source);
+        assert_eq!(
+            names(&items),
+            [
+                ("inner", Visibility::Private),
+                ("hidden", Visibility::Private),
+                ("deeper", Visibility::Private),
+                ("also_hidden", Visibility::Private),
+                ("outer", Visibility::Public),
+                ("shown", Visibility::Public),
+                ("scoped", Visibility::Private),
+                ("macros", Visibility::Private),
+                ("exported", Visibility::Public),
+                ("file", Visibility::Private),
+                ("other", Visibility::Public),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_inner_docs() {
+        let source = r#"
+//! the crate
+#![doc = "more"]
+
+/// outer
+pub mod inline {
+    //! inner
+    #![doc = "attribute"]
+
+    pub fn inside() {}
+}
+
+pub mod file;
+"#;
+        let (items, _, docs) = parse(source);
+        assert_eq!(docs, ["the crate", "more"]);
+        assert_eq!(items[0].kind, ClassifyKind::Module);
+        assert_eq!(items[0].docs, ["outer", "inner", "attribute"]);
+        assert!(items[1].docs.is_empty());
+        assert_eq!(items[2].signature, "pub mod file;");
+        assert!(!items[2].is_documented());
+    }
+
+    #[test]
+    fn parse_attributes() {
+        let source = "
+#![allow(missing_docs)]
+
+#[cfg(feature = \"foo\")]
+pub fn gated() {}
+
+#[allow(missing_docs)]
+pub mod module {
+    pub fn inside() {}
+}
+";
+        let (_, attributes, _) = parse(source);
+        let spans = attributes
+            .iter()
+            .map(|attr| (attr.inner, attr.start, attr.end))
+            .collect::<Vec<_>>();
+        assert_eq!(spans, [(true, 1, 10), (false, 5, 5), (false, 8, 10)]);
+    }
+
+    #[test]
+    fn item_paths() {
+        let source = r#"
+pub struct Thing {
+    pub field: u8,
+}
+
+const TEXT: &str = "impl Other {
+";
+
+impl<T> Trait for Thing<T> {
+    /// docs
+    fn method(&self) {}
+}
+
+mod inner {
+    pub enum Kind {
+        First,
+    }
+}
+"#;
+        assert_eq!(item_path(source, 2).as_deref(), Some("Thing"));
+        assert_eq!(item_path(source, 3).as_deref(), Some("Thing::field"));
+        assert_eq!(item_path(source, 10).as_deref(), Some("Thing::method"));
+        assert_eq!(item_path(source, 16).as_deref(), Some("inner::Kind::First"));
+    }
+
+    #[test]
+    fn decls() {
+        let decl = Decl::parse("pub const unsafe fn raw(&self) {").unwrap();
+        assert_eq!(decl.visibility, Visibility::Public);
+        assert!(decl.keyword == Keyword::Fn && decl.is_unsafe);
+        assert_eq!(decl.name, "raw");
+
+        let decl = Decl::parse("pub(crate) static mut COUNT: usize = 0;").unwrap();
+        assert_eq!(decl.visibility, Visibility::Private);
+        assert!(decl.keyword == Keyword::Static);
+        assert_eq!(decl.name, "COUNT");
+
+        let decl = Decl::parse("pub trait Alias = Clone + Send;").unwrap();
+        assert!(decl.keyword == Keyword::TraitAlias);
+
+        let decl = Decl::parse("macro_rules! r#try {").unwrap();
+        assert!(decl.keyword == Keyword::Macro);
+        assert_eq!(decl.name, "try");
+
+        let decl = Decl::parse("pub extern \"C\" fn callback() {").unwrap();
+        assert_eq!(decl.name, "callback");
+
+        assert!(Decl::parse("let x = 1;").is_none());
+        assert!(Decl::parse("impl Foo {").is_none());
+        assert!(Decl::parse("pub use foo::bar;").is_none());
+    }
+}
@@ -1,11 +1,13 @@
 use std::{
-    collections::{BTreeMap, HashSet},
-    path::PathBuf,
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
 };
 
-use chorts::{data::Text, Filename, Highlight, Visit, Visitor};
-
-use crate::args::ClassifyKind;
+use crate::{
+    args::{ClassifyKind, Visibility},
+    blame::Blame,
+    lints::{Level, Levels},
+};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub(crate) struct Spanned<T> {
@@ -15,109 +17,526 @@ pub(crate) struct Spanned<T> {
 }
 
 impl<T> Spanned<T> {
-    const fn new(item: T, row: usize, col: usize) -> Self {
+    pub(crate) const fn new(item: T, row: usize, col: usize) -> Self {
         Self { item, row, col }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+pub struct Snippet {
+    pub data: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Debug)]
 pub struct Missing {
     pub message: Spanned<String>,
-    pub text: Vec<Highlight<'static>>,
+    pub text: Vec<Snippet>,
+    pub visibility: Visibility,
+    pub label: Option<String>,
+    pub code: String,
+    // the level the project configured for this lint
+    pub level: Option<Level>,
+    // the runs this was reported by, like `lib` or `bin cds`
+    pub origins: Vec<String>,
+    // who last changed this line, and when
+    pub blame: Option<Blame>,
+}
+
+pub const QUALITY_LINTS: &[&str] = &[
+    "clippy::doc_markdown",
+    "clippy::doc_lazy_continuation",
+    "clippy::doc_link_with_quotes",
+    "clippy::doc_overindented_list_items",
+    "clippy::empty_line_after_doc_comments",
+    "clippy::needless_doctest_main",
+    "clippy::tabs_in_doc_comments",
+    "clippy::too_long_first_doc_paragraph",
+];
+
+pub const RUSTDOC_LINTS: &[&str] = &[
+    "rustdoc::broken_intra_doc_links",
+    "rustdoc::private_intra_doc_links",
+    "rustdoc::bare_urls",
+    "rustdoc::invalid_html_tags",
+    "rustdoc::invalid_rust_codeblocks",
+    "rustdoc::missing_crate_level_docs",
+];
+
+// rustc reports lints with underscores, but they can be written with hyphens too
+pub fn normalize_lint(lint: &str) -> String {
+    lint.replace('-', "_")
+}
+
+// modules from `#[path]` are reported like `src/bin/../shared.rs`, which is the same file as `src/shared.rs`
+pub fn normalize_path(file: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in file.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                out.pop();
+            }
+            component => out.push(component),
+        }
+    }
+    out
+}
+
+// the tool can be left out, so `missing_errors_doc` matches `clippy::missing_errors_doc`
+pub fn lint_matches(lint: &str, code: &str) -> bool {
+    let lint = normalize_lint(lint);
+    lint == code || code.rsplit_once("::").is_some_and(|(_, name)| name == lint)
+}
+
+// `*` shouldn't cross directories, like when globbing on disk
+const MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// the files to focus, matched against each diagnostic as it arrives
+#[derive(Clone, Debug, Default)]
+pub struct FileFilter {
+    include: Vec<glob::Pattern>,
+    exclude: Vec<glob::Pattern>,
+    manifest_dir: PathBuf,
+    workspace_root: PathBuf,
+}
+
+impl FileFilter {
+    /// patterns starting with `!` are added to the excluded patterns
+    pub fn new(
+        patterns: impl IntoIterator<Item = String>,
+        excluded: impl IntoIterator<Item = String>,
+    ) -> anyhow::Result<Self> {
+        let compile = |pattern: &str| {
+            glob::Pattern::new(pattern)
+                .map_err(|err| anyhow::anyhow!("invalid glob: {pattern}: {err}"))
+        };
+
+        let mut this = Self::default();
+        for pattern in patterns {
+            match pattern.strip_prefix('!') {
+                Some(pattern) => this.exclude.push(compile(pattern)?),
+                None => this.include.push(compile(&pattern)?),
+            }
+        }
+        for pattern in excluded {
+            this.exclude.push(compile(&pattern)?)
+        }
+        Ok(this)
+    }
+
+    /// the patterns are matched relative to either of these directories
+    pub fn with_roots(mut self, manifest_dir: PathBuf, workspace_root: PathBuf) -> Self {
+        self.manifest_dir = manifest_dir;
+        self.workspace_root = workspace_root;
+        self
+    }
+
+    // rustc reports paths relative to the workspace root, or absolute paths for other crates
+    fn candidates(&self, file: &str) -> Vec<PathBuf> {
+        let file = Path::new(file);
+        let absolute = self.workspace_root.join(file);
+        let relative = [&self.manifest_dir, &self.workspace_root]
+            .into_iter()
+            .filter_map(|root| absolute.strip_prefix(root).ok());
+        std::iter::once(file)
+            .chain(relative)
+            .map(Path::to_path_buf)
+            .collect()
+    }
+
+    // the results are keyed by their path relative to the workspace root, like rustc reports them
+    pub fn read_source(&self, file: &Path) -> Option<String> {
+        std::fs::read_to_string(self.locate(file)?).ok()
+    }
+
+    /// the path of `file` on disk
+    pub fn locate(&self, file: &Path) -> Option<PathBuf> {
+        Some(self.workspace_root.join(file)).filter(|path| path.is_file())
+    }
+
+    /// the path of `file` from the package, relative to the workspace root like rustc reports it
+    pub fn package_path(&self, file: &Path) -> String {
+        let absolute = self.manifest_dir.join(file);
+        let relative = absolute
+            .strip_prefix(&self.workspace_root)
+            .unwrap_or(&absolute);
+        relative.to_string_lossy().to_string()
+    }
+
+    // spans can also point into other crates, the standard library or at macros without a file
+    fn is_local(&self, file: &str) -> bool {
+        if file.starts_with('<') {
+            return false;
+        }
+        let file = Path::new(file);
+        file.is_relative()
+            || file.starts_with(&self.workspace_root)
+            || file.starts_with(&self.manifest_dir)
+    }
+
+    fn matches(&self, file: &str) -> bool {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return true;
+        }
+
+        let candidates = self.candidates(file);
+        let any = |patterns: &[glob::Pattern]| {
+            candidates.iter().any(|file| {
+                patterns
+                    .iter()
+                    .any(|p| p.matches_path_with(file, MATCH_OPTIONS))
+            })
+        };
+
+        (self.include.is_empty() || any(&self.include)) && !any(&self.exclude)
+    }
+}
+
+/// the items to focus, by their name or their path
+#[derive(Clone, Debug, Default)]
+pub struct ItemFilter {
+    patterns: Vec<glob::Pattern>,
+    regexes: Vec<regex::Regex>,
+}
+
+impl ItemFilter {
+    pub fn new(
+        patterns: impl IntoIterator<Item = String>,
+        regexes: impl IntoIterator<Item = String>,
+    ) -> anyhow::Result<Self> {
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| {
+                glob::Pattern::new(&pattern)
+                    .map_err(|err| anyhow::anyhow!("invalid item pattern: {pattern}: {err}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let regexes = regexes
+            .into_iter()
+            .map(|re| {
+                regex::Regex::new(&re)
+                    .map_err(|err| anyhow::anyhow!("invalid item regex: {re}: {err}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { patterns, regexes })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty() && self.regexes.is_empty()
+    }
+
+    // `Mapping::dispatch` is matched by `dispatch` and `Mapping::*`
+    fn matches(&self, path: &str) -> bool {
+        let name = path.rsplit("::").next().unwrap_or(path);
+        self.patterns
+            .iter()
+            .any(|p| p.matches(name) || p.matches(path))
+            || self
+                .regexes
+                .iter()
+                .any(|re| re.is_match(name) || re.is_match(path))
+    }
+}
+
+/// include and exclude kinds, but only for some files
+///
+/// when both are empty the files are ignored entirely
+#[derive(Clone, Debug)]
+pub struct PathFilter {
+    pub paths: Vec<glob::Pattern>,
+    pub include: Vec<ClassifyKind>,
+    pub exclude: Vec<ClassifyKind>,
+}
+
+impl PathFilter {
+    fn applies_to(&self, candidates: &[PathBuf]) -> bool {
+        candidates.iter().any(|file| {
+            self.paths
+                .iter()
+                .any(|p| p.matches_path_with(file, MATCH_OPTIONS))
+        })
+    }
+
+    fn allows(&self, message: &str) -> bool {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return false;
+        }
+        if !self.include.is_empty() && !self.include.iter().any(|c| c.matches(message)) {
+            return false;
+        }
+        !self.exclude.iter().any(|c| c.matches(message))
+    }
 }
 
 #[derive(Default)]
-pub struct MissingDocs<'a> {
+pub struct MissingDocs {
     pub map: BTreeMap<PathBuf, Vec<Missing>>,
-    pub last: Option<String>,
-    set: HashSet<&'a PathBuf>,
-    include: Vec<&'static str>, // this is exclusive
-    exclude: Vec<&'static str>, // this is inclusive
+    // the owners of each file, from `CODEOWNERS`
+    pub owners: BTreeMap<PathBuf, Vec<String>>,
+    origin: Option<String>,
+    // the target of the diagnostic being reported, with `--show-origin`
+    target: Option<String>,
+    show_targets: bool,
+    files: FileFilter,
+    include: Vec<ClassifyKind>, // this is exclusive
+    exclude: Vec<ClassifyKind>, // this is inclusive
+    visibility: Option<Visibility>,
+    lints: Vec<String>,
+    levels: Levels,
+    path_filters: Vec<PathFilter>,
+    codes: Vec<String>,
 }
 
-impl<'a> MissingDocs<'a> {
+impl MissingDocs {
     pub fn new(
-        set: HashSet<&'a PathBuf>,
+        files: FileFilter,
         include: impl IntoIterator<Item = ClassifyKind>,
         exclude: impl IntoIterator<Item = ClassifyKind>,
+        visibility: Option<Visibility>,
     ) -> Self {
         Self {
             map: BTreeMap::new(),
-            last: None,
-            set,
-            include: include.into_iter().map(|s| s.as_str()).collect(),
-            exclude: exclude.into_iter().map(|s| s.as_str()).collect(),
+            owners: BTreeMap::new(),
+            origin: None,
+            target: None,
+            show_targets: false,
+            files,
+            include: include.into_iter().collect(),
+            exclude: exclude.into_iter().collect(),
+            visibility,
+            lints: Vec::new(),
+            levels: Levels::default(),
+            path_filters: Vec::new(),
+            codes: Vec::new(),
         }
     }
 
-    fn filter_message(&self, msg: &chorts::data::Message) -> bool {
-        let Some(code) = &msg.code else { return false };
-        match &*code.code {
+    pub fn with_lints(mut self, lints: impl IntoIterator<Item = String>) -> Self {
+        self.lints.extend(lints);
+        self
+    }
+
+    /// labels the results with the targets they came from
+    pub fn with_targets(mut self, show_targets: bool) -> Self {
+        self.show_targets = show_targets;
+        self
+    }
+
+    pub fn with_levels(mut self, levels: Levels) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    pub fn with_path_filters(mut self, filters: Vec<PathFilter>) -> Self {
+        self.path_filters = filters;
+        self
+    }
+
+    /// only keeps the diagnostics from these lints
+    pub fn with_codes(mut self, codes: impl IntoIterator<Item = String>) -> Self {
+        self.codes.extend(codes);
+        self
+    }
+
+    fn visibility_of(code: &str) -> Visibility {
+        match code {
+            "clippy::missing_docs_in_private_items" => Visibility::Private,
+            _ => Visibility::Public,
+        }
+    }
+
+    fn filter(&self, code: &str, message: &str) -> bool {
+        match code {
             "missing_docs"
+            | "clippy::missing_docs_in_private_items"
             | "clippy::empty_docs"
             | "clippy::suspicious_doc_comments"
-            | "clippy::missing-errors-doc"
-            | "clippy::missing-panics-doc"
-            | "clippy::missing-safety-doc"
+            | "clippy::missing_errors_doc"
+            | "clippy::missing_panics_doc"
+            | "clippy::missing_safety_doc"
             | "clippy::unnecessary_safety_doc"
             | "clippy::undocumented_unsafe_blocks" => {}
+            code if QUALITY_LINTS.contains(&code) || RUSTDOC_LINTS.contains(&code) => {}
+            code if self.lints.iter().any(|c| c == code) => {}
+            // these are produced by cds itself
+            code if code.starts_with("cds::") => {}
             _ => return false,
         };
 
+        if !self.codes.is_empty() && !self.codes.iter().any(|c| lint_matches(c, code)) {
+            return false;
+        }
+
         // the empty check because any([]) == true
-        if !self.include.is_empty() && !self.include.iter().any(|c| msg.message.ends_with(c)) {
+        if !self.include.is_empty() && !self.include.iter().any(|c| c.matches(message)) {
             return false;
         }
 
-        if !self.exclude.is_empty() && self.exclude.iter().any(|c| msg.message.ends_with(c)) {
+        if !self.exclude.is_empty() && self.exclude.iter().any(|c| c.matches(message)) {
             return false;
         }
 
         true
     }
-}
 
-impl Visitor for MissingDocs<'_> {
-    fn visit_message(&mut self, message: &chorts::data::Message) {
-        if self.filter_message(message) {
-            self.last = Some(message.message.clone());
-            message.spans.accept(self);
+    pub fn is_focused(&self, file: &str) -> bool {
+        self.files.matches(file)
+    }
+
+    /// whether `file` is in this workspace, rather than another crate or the standard library
+    pub fn is_local(&self, file: &str) -> bool {
+        self.files.is_local(file)
+    }
+
+    fn filter_path(&self, file: &str, message: &str) -> bool {
+        if self.path_filters.is_empty() {
+            return true;
         }
+
+        let candidates = self.files.candidates(file);
+        self.path_filters
+            .iter()
+            .filter(|filter| filter.applies_to(&candidates))
+            .all(|filter| filter.allows(message))
     }
 
-    fn visit_span(&mut self, file: Filename<'_>, text: &[Text]) {
-        if !self.set.is_empty() && !self.set.contains(&PathBuf::from(&*file.name)) {
-            let _ = self.last.take();
+    /// adds a diagnostic
+    ///
+    /// `file` is relative to the workspace root, see [`Self::package_path`] for files from the package
+    pub fn report(
+        &mut self,
+        file: &str,
+        code: &str,
+        message: Spanned<String>,
+        text: Vec<Snippet>,
+        label: Option<String>,
+    ) {
+        let code = normalize_lint(code);
+        let path = normalize_path(Path::new(file));
+        let file = &*path.to_string_lossy();
+        if !self.is_focused(file)
+            || !self.filter(&code, &message.item)
+            || !self.filter_path(file, &message.item)
+        {
             return;
         }
 
-        let last = self.last.take();
-        let last = last.map(|value| Spanned::new(value, file.row, file.col));
+        let missing = Missing {
+            message,
+            text,
+            visibility: Self::visibility_of(&code),
+            label,
+            code,
+            level: None,
+            origins: Vec::new(),
+            blame: None,
+        };
+        self.push(path, missing);
+    }
+
+    pub fn read_source(&self, file: &Path) -> Option<String> {
+        self.files.read_source(file)
+    }
+
+    pub fn locate(&self, file: &Path) -> Option<PathBuf> {
+        self.files.locate(file)
+    }
+
+    /// the path to report for `file`, which is relative to the package
+    pub fn package_path(&self, file: &Path) -> String {
+        self.files.package_path(file)
+    }
+
+    /// the run the next diagnostics come from
+    pub fn set_origin(&mut self, origin: Option<String>) {
+        self.origin = origin;
+    }
+
+    /// the target the next diagnostics come from
+    pub fn set_target(&mut self, target: Option<String>) {
+        self.target = target.filter(|_| self.show_targets);
+    }
+
+    /// only keeps the results with the `--visibility` that was asked for
+    ///
+    /// this runs after the results are merged, since clippy reports public items as private too
+    pub fn retain_visibility(&mut self) {
+        let Some(visibility) = self.visibility else {
+            return;
+        };
+
+        for list in self.map.values_mut() {
+            list.retain(|missing| missing.visibility == visibility);
+        }
+        self.map.retain(|_, list| !list.is_empty());
+    }
 
-        #[derive(Default)]
-        struct TextCollector {
-            inner: Vec<Highlight<'static>>,
+    /// only keeps the items matching `filter`, their paths are found by reading the source
+    pub fn retain_items(&mut self, filter: &ItemFilter) {
+        if filter.is_empty() {
+            return;
         }
 
-        impl Visitor for TextCollector {
-            fn visit_text(&mut self, text: Highlight<'_>) {
-                self.inner.push(text.to_owned());
-            }
+        let files = &self.files;
+        for (file, list) in &mut self.map {
+            let source = files.read_source(file).unwrap_or_default();
+
+            list.retain(|missing| {
+                crate::source::item_path(&source, missing.message.row)
+                    .is_some_and(|path| filter.matches(&path))
+            });
         }
 
-        let mut tv = TextCollector::default();
-        for text in text {
-            text.accept(&mut tv);
+        self.map.retain(|_, list| !list.is_empty());
+    }
+
+    fn push(&mut self, file: PathBuf, mut missing: Missing) {
+        missing.level = self.levels.get(&missing.code);
+        let origin = match (&self.target, &self.origin) {
+            (Some(target), Some(features)) => Some(format!("{target} with {features}")),
+            (target, features) => target.clone().or_else(|| features.clone()),
+        };
+        missing.origins.extend(origin);
+
+        let list = self.map.entry(file).or_default();
+
+        // a module compiled into several targets, or a macro invoked several times,
+        // reports the same lint at the same place more than once
+        let duplicate = |other: &Missing| {
+            other.message.row == missing.message.row
+                && other.message.col == missing.message.col
+                && other.message.item == missing.message.item
+                && other.code == missing.code
+        };
+        if let Some(other) = list.iter_mut().find(|other| duplicate(other)) {
+            for origin in missing.origins {
+                if !other.origins.contains(&origin) {
+                    other.origins.push(origin);
+                }
+            }
+            return;
         }
 
-        let missing = Missing {
-            message: last.expect("valid tree"),
-            text: tv.inner,
+        let same = |other: &Missing| {
+            other.message.row == missing.message.row
+                && other.message.col == missing.message.col
+                && other.message.item == missing.message.item
         };
 
-        self.map
-            .entry(PathBuf::from(file.name.to_string()))
-            .or_default()
-            .push(missing);
+        // clippy also reports public items when checking private items,
+        // so the public lint wins when both are attached to the same item
+        match list.iter().position(same) {
+            Some(pos) if missing.visibility == Visibility::Public => list[pos] = missing,
+            Some(_) => {}
+            None => list.push(missing),
+        }
     }
 }